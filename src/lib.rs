@@ -3,14 +3,50 @@
 //!
 //! It is primarily used to manage [`Bytes`] and [`BytesMut`] and make a [`&[IoSlice<'_>]`]
 //! to be used by `writev`.
-use std::{collections::VecDeque, io::IoSlice};
+#![cfg_attr(feature = "safe-only", deny(unsafe_code))]
+use std::{
+    collections::VecDeque,
+    io::IoSlice,
+    ops::{ControlFlow, Range},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use faststr::FastStr;
-use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    time::Instant,
+};
 
 const DEFAULT_BUFFER_SIZE: usize = 8192; // 8KB
 const DEFAULT_DEQUE_SIZE: usize = 16;
+const DEFAULT_DEQUE_SHRINK_THRESHOLD: usize = 4 * DEFAULT_DEQUE_SIZE;
+// Capacities larger than this are rounded up to a multiple of it, see `recommended_capacity`.
+const PAGE_SIZE: usize = 4096; // 4KB
+
+// If `put_slice` has to grow a bytes_mut holding at least this many bytes, the bytes_mut is
+// sealed into the list instead, so the existing content is not copied again.
+const SEAL_ON_GROW_LEN: usize = 64 * 1024; // 64KB
+
+// Chunks shorter than this are copied by `from_buf` instead of becoming separate nodes.
+const MIN_ZERO_COPY_LEN: usize = 1024; // 1KB
+
+// The max length of a varint encoded `u64`.
+const MAX_VARINT_LEN: usize = 10;
+
+// The max number of io slices passed to a single `write_vectored` call, this is `IOV_MAX` on
+// most unix platforms. `WSASend` on Windows has no such limit, but we keep the same cap so
+// that a single call won't pin too many buffers.
+const MAX_IOVS_PER_WRITE: usize = 1024;
+// The max length of an io slice, `IoSlice::new` panics with a larger slice on Windows since
+// `WSABUF::len` is a `u32`.
+#[cfg(windows)]
+const MAX_IOSLICE_LEN: usize = u32::MAX as usize;
+#[cfg(not(windows))]
+const MAX_IOSLICE_LEN: usize = usize::MAX;
 
 pub struct LinkedBytes {
     // This is used to avoid allocating a new Vec when calling `as_ioslice`.
@@ -18,16 +54,318 @@ pub struct LinkedBytes {
     // so we just use `'static` here.
     // [`ioslice`] must be the first field, so that it is dropped before [`list`]
     // and [`bytes`] to keep soundness.
+    // The self-referential io slices are left out with the `safe-only` feature.
+    #[cfg(not(feature = "safe-only"))]
     ioslice: Vec<IoSlice<'static>>,
+    // The io slices built by `io_slice_cached`, self-referential as well.
+    #[cfg(not(feature = "safe-only"))]
+    io_slice_cache: Vec<IoSlice<'static>>,
+    // The generation, and the pointer and length of [`bytes`] `io_slice_cache` was built for.
+    #[cfg(not(feature = "safe-only"))]
+    io_slice_cache_key: Option<(u64, usize, usize)>,
+    // Bumped on every change of [`list`], to invalidate `io_slice_cache`.
+    generation: u64,
+    // The generation and the pointer of [`bytes`] right after the last node was sealed from it,
+    // so the two are adjacent in one allocation as long as neither has changed, see
+    // `merge_sealed_tail`.
+    #[cfg(not(feature = "safe-only"))]
+    sealed_tail: Option<(u64, usize)>,
+    // `reset` shrinks [`list`] if its capacity exceeds this.
+    deque_shrink_threshold: usize,
 
     bytes: BytesMut,
     list: VecDeque<Node>,
+    // Cached total length of the nodes in [`list`], not including [`bytes`].
+    list_len: usize,
+    // Set by `get_list_mut`, whose node may be resized, until [`list_len`] is recomputed.
+    list_len_dirty: bool,
+    // Number of nodes pushed to the front of [`list`], used to locate front slots.
+    front_count: usize,
+    // Bumped whenever nodes pushed to the front are removed, to invalidate the front slot handles.
+    front_epoch: u64,
+    // The ids and node indices of the varint slots and placeholders not filled yet, see
+    // `register_slot`.
+    slots: Vec<(u64, usize)>,
+    // The id of the next slot, never reused so a stale handle can't match a later slot.
+    next_slot_id: u64,
+    // Freed `BytesMut` nodes kept for reuse, at most `node_pool_size` of them.
+    node_pool: Vec<BytesMut>,
+    node_pool_size: usize,
+    // Index of the first node of the current frame, see `begin_frame`.
+    frame_start: usize,
+    // If set, inserts copy into [`bytes`] instead of creating nodes.
+    contiguous: bool,
+    // Tuning of the write path, see `set_writev_strategy`.
+    writev_strategy: WritevStrategy,
+    // Capacity given to a full [`bytes`] split on insert, `None` for the capacity it had.
+    carry_over_capacity: Option<usize>,
+    // Number of buffers allocated by the carry-over since the last `reset`, which can't be merged
+    // back with the full ones.
+    carried_over: usize,
+    // Max total length allowed through `BufMut`, see `set_size_limit`.
+    size_limit: Option<usize>,
+    // Shared counter of the inserted bytes, see `with_accounting`.
+    accounting: Option<Arc<AtomicUsize>>,
+    // Bytes added to [`accounting`] by this instance and not released yet.
+    accounted: usize,
+}
+
+/// The error returned by [`LinkedBytes::write_all_vectored_detailed`] and
+/// [`LinkedBytes::sync_write_all_vectored_detailed`].
+///
+/// Besides the underlying [`std::io::Error`], it records where the write failed.
+#[derive(Debug)]
+pub struct WriteError {
+    error: std::io::Error,
+    bytes_written: usize,
+    last_chunk_index: usize,
+}
+
+impl WriteError {
+    #[inline]
+    fn new(error: std::io::Error, bytes_written: usize, last_chunk_index: usize) -> Self {
+        Self {
+            error,
+            bytes_written,
+            last_chunk_index,
+        }
+    }
+
+    /// Returns the underlying io error.
+    #[inline]
+    pub fn error(&self) -> &std::io::Error {
+        &self.error
+    }
+
+    /// Consumes the `WriteError`, returning the underlying io error.
+    #[inline]
+    pub fn into_inner(self) -> std::io::Error {
+        self.error
+    }
+
+    /// Returns the number of bytes that have been written before the failure.
+    #[inline]
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Returns the index of the chunk being written when the failure occurred.
+    ///
+    /// Chunks are counted as in [`LinkedBytes::iter_chunks`], so empty nodes are skipped.
+    #[inline]
+    pub fn last_chunk_index(&self) -> usize {
+        self.last_chunk_index
+    }
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (after {} bytes, at chunk {})",
+            self.error, self.bytes_written, self.last_chunk_index
+        )
+    }
+}
+
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<WriteError> for std::io::Error {
+    #[inline]
+    fn from(e: WriteError) -> Self {
+        e.error
+    }
+}
+
+/// The error returned by [`LinkedBytes::try_reset`] when some split buffers couldn't be merged
+/// back, so their memory has been freed instead of reused.
+///
+/// The buffer is reset anyway, this only reports the lost reuse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetError {
+    reclaimed_bytes: usize,
+}
+
+impl ResetError {
+    /// Returns the capacity of the current bytes_mut after reset, see
+    /// [`ResetReport::reclaimed_bytes`].
+    #[inline]
+    pub fn reclaimed_bytes(&self) -> usize {
+        self.reclaimed_bytes
+    }
+}
+
+impl std::fmt::Display for ResetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "memory not fully reclaimed on reset ({} bytes reclaimed)",
+            self.reclaimed_bytes
+        )
+    }
+}
+
+impl std::error::Error for ResetError {}
+
+/// The tuning of the write path, set by [`LinkedBytes::set_writev_strategy`].
+///
+/// The default keeps the write path as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WritevStrategy {
+    /// The max number of io slices handed to a single `write_vectored` call, `IOV_MAX` (1024) by
+    /// default.
+    pub iov_max: usize,
+    /// Content fragmented into several chunks is copied into one buffer and written at once by
+    /// `write_all_vectored` and `sync_write_all_vectored` if it's not longer than this, `0` (the
+    /// default) disables it.
+    pub contiguous_fallback_threshold: usize,
+    /// See [`LinkedBytes::set_merge_trailing_threshold`], `0` (the default) disables it.
+    pub merge_small_threshold: usize,
+    /// The max number of bytes handed to a single `write_vectored` call, `usize::MAX` (the
+    /// default) for no limit. At least one io slice is handed over anyway, however long it is.
+    pub max_batch_bytes: usize,
+}
+
+impl WritevStrategy {
+    const DEFAULT: Self = Self {
+        iov_max: MAX_IOVS_PER_WRITE,
+        contiguous_fallback_threshold: 0,
+        merge_small_threshold: 0,
+        max_batch_bytes: usize::MAX,
+    };
+
+    /// Returns how many of the first io slices to hand to the writer in one call.
+    fn window(&self, slices: &[IoSlice<'_>]) -> usize {
+        let slices = &slices[..slices.len().min(self.iov_max.max(1))];
+        let mut bytes = 0;
+        let window = slices
+            .iter()
+            .take_while(|slice| {
+                bytes = add_len(bytes, slice.len());
+                bytes <= self.max_batch_bytes
+            })
+            .count();
+        window.max(1).min(slices.len())
+    }
+}
+
+impl Default for WritevStrategy {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A handle to a slot reserved by [`LinkedBytes::reserve_front_slot`].
+///
+/// It's invalidated by `reset`, `clear` or any removal of the nodes pushed to the front (e.g. by
+/// `consume_front_nodes` or `split_off_bytes`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrontSlotHandle {
+    seq: usize,
+    len: usize,
+    epoch: u64,
+}
+
+/// A handle to a varint slot reserved by [`LinkedBytes::reserve_varint_slot`].
+///
+/// It stays valid across the following inserts, prepends and removals of the nodes before the
+/// slot, and is invalidated by `reset`, `clear` or the removal of the slot itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarintSlotHandle {
+    id: u64,
+    offset: usize,
+}
+
+/// A handle to a placeholder inserted by [`LinkedBytes::insert_placeholder`].
+///
+/// It stays valid across the following inserts, prepends and removals of the nodes before the
+/// placeholder, and is invalidated by `reset`, `clear` or the removal of the placeholder itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaceholderHandle {
+    id: u64,
+}
+
+/// The nodes of a frame built between [`LinkedBytes::begin_frame`] and
+/// [`LinkedBytes::end_frame`].
+///
+/// It's invalidated by any change to the front of the list, by `reset` or `clear`, and by
+/// rotating an earlier frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameView {
+    start: usize,
+    end: usize,
+}
+
+/// What [`LinkedBytes::reset_with_report`] has done with the memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetReport {
+    /// Whether all the split buffers have been merged back into one allocation. It's `false` if
+    /// some of them were no longer contiguous (e.g. the current bytes_mut was reallocated).
+    ///
+    /// The buffers allocated when a full bytes_mut is split on insert (see
+    /// [`LinkedBytes::set_carry_over_capacity`]) are expected to be separate, so they don't count.
+    pub reused: bool,
+    /// The capacity of the current bytes_mut after reset, available for the next writes.
+    pub reclaimed_bytes: usize,
+}
+
+/// A separate node reserved by [`LinkedBytes::reserve_as_node`], which derefs to the
+/// [`BytesMut`] to write into.
+///
+/// The node is appended to the list when this is dropped.
+pub struct ReservedNode<'a> {
+    linked: &'a mut LinkedBytes,
+    buf: BytesMut,
 }
 
+/// A node of the list.
+///
+/// Every node is backed by memory and exposes its content as `&[u8]`, which is what the io
+/// slices are built from. File-backed regions (e.g. for `sendfile` or `splice`) can't be
+/// represented, read them into a buffer first.
+///
+/// More variants may be added, so matches on it need a wildcard arm.
+#[non_exhaustive]
 pub enum Node {
     Bytes(Bytes),
     BytesMut(BytesMut),
     FastStr(FastStr),
+    Static(&'static [u8]),
+    /// Memory managed outside, see [`LinkedBytes::insert_borrowed_unchecked`].
+    #[cfg(not(feature = "safe-only"))]
+    Raw(RawSlice),
+}
+
+/// A pointer and length of memory managed outside, held by [`Node::Raw`].
+///
+/// It can only be created by the unsafe [`LinkedBytes::insert_borrowed_unchecked`], whose caller
+/// guarantees that the memory outlives the node, so it's not available with the `safe-only`
+/// feature.
+#[cfg(not(feature = "safe-only"))]
+pub struct RawSlice {
+    ptr: *const u8,
+    len: usize,
+}
+
+// SAFETY: the caller of `insert_borrowed_unchecked` guarantees the memory can be read from any
+// thread while the node is alive, and it's never written through the node
+#[cfg(not(feature = "safe-only"))]
+unsafe impl Send for RawSlice {}
+#[cfg(not(feature = "safe-only"))]
+unsafe impl Sync for RawSlice {}
+
+#[cfg(not(feature = "safe-only"))]
+impl RawSlice {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: guaranteed by the caller of `insert_borrowed_unchecked`
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
 }
 
 impl AsRef<[u8]> for Node {
@@ -37,6 +375,78 @@ impl AsRef<[u8]> for Node {
             Node::Bytes(b) => b.as_ref(),
             Node::BytesMut(b) => b.as_ref(),
             Node::FastStr(s) => s.as_ref(),
+            Node::Static(s) => s,
+            #[cfg(not(feature = "safe-only"))]
+            Node::Raw(raw) => raw.as_slice(),
+        }
+    }
+}
+
+impl Node {
+    /// Returns the content as `&str` if it's valid UTF-8.
+    ///
+    /// `FastStr` nodes are always valid UTF-8, the other variants are checked.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Node::FastStr(s) => Some(s.as_str()),
+            _ => std::str::from_utf8(self.as_ref()).ok(),
+        }
+    }
+
+    /// Splits the node into two at the given index, `self` keeps `[0, at)` and the returned node
+    /// contains `[at, len)`.
+    ///
+    /// `FastStr` nodes are turned into `Bytes` nodes, since `at` may not be on a char boundary.
+    fn split_off(&mut self, at: usize) -> Node {
+        match self {
+            Node::Bytes(b) => Node::Bytes(b.split_off(at)),
+            Node::BytesMut(b) => Node::BytesMut(b.split_off(at)),
+            Node::FastStr(s) => {
+                // cloning a `FastStr` is cheap
+                let mut head = s.clone().into_bytes();
+                let tail = head.split_off(at);
+                *self = Node::Bytes(head);
+                Node::Bytes(tail)
+            }
+            Node::Static(s) => {
+                let (head, tail) = s.split_at(at);
+                *self = Node::Static(head);
+                Node::Static(tail)
+            }
+            #[cfg(not(feature = "safe-only"))]
+            Node::Raw(raw) => {
+                assert!(at <= raw.len, "split_off out of bounds");
+                let tail = RawSlice {
+                    // SAFETY: `at` is in bounds
+                    ptr: unsafe { raw.ptr.add(at) },
+                    len: raw.len - at,
+                };
+                raw.len = at;
+                Node::Raw(tail)
+            }
+        }
+    }
+
+    /// Returns the length of the content if it's not a `BytesMut`, that is held without copying.
+    #[inline]
+    fn shared_len(&self) -> usize {
+        match self {
+            Node::BytesMut(_) => 0,
+            _ => self.as_ref().len(),
+        }
+    }
+
+    /// Converts the node into `Bytes`, without copying except for `Raw` nodes, whose memory
+    /// isn't owned.
+    fn into_bytes(self) -> Bytes {
+        match self {
+            Node::Bytes(b) => b,
+            Node::BytesMut(b) => b.freeze(),
+            Node::FastStr(s) => s.into_bytes(),
+            Node::Static(s) => Bytes::from_static(s),
+            #[cfg(not(feature = "safe-only"))]
+            Node::Raw(raw) => Bytes::copy_from_slice(raw.as_slice()),
         }
     }
 }
@@ -49,277 +459,5955 @@ impl LinkedBytes {
 
     #[inline]
     pub fn with_capacity(cap: usize) -> Self {
-        let bytes = BytesMut::with_capacity(cap);
+        let bytes = BytesMut::with_capacity(Self::recommended_capacity(cap));
         let list = VecDeque::with_capacity(DEFAULT_DEQUE_SIZE);
         Self {
             list,
             bytes,
+            #[cfg(not(feature = "safe-only"))]
             ioslice: Vec::with_capacity(DEFAULT_DEQUE_SIZE),
+            list_len: 0,
+            list_len_dirty: false,
+            front_count: 0,
+            front_epoch: 0,
+            slots: Vec::new(),
+            next_slot_id: 0,
+            node_pool: Vec::new(),
+            node_pool_size: 0,
+            frame_start: 0,
+            contiguous: false,
+            writev_strategy: WritevStrategy::DEFAULT,
+            carry_over_capacity: None,
+            carried_over: 0,
+            size_limit: None,
+            accounting: None,
+            accounted: 0,
+            #[cfg(not(feature = "safe-only"))]
+            io_slice_cache: Vec::new(),
+            #[cfg(not(feature = "safe-only"))]
+            io_slice_cache_key: None,
+            generation: 0,
+            #[cfg(not(feature = "safe-only"))]
+            sealed_tail: None,
+            deque_shrink_threshold: DEFAULT_DEQUE_SHRINK_THRESHOLD,
         }
     }
 
-    #[inline]
-    pub fn bytes(&self) -> &BytesMut {
-        &self.bytes
-    }
-
-    #[inline]
-    pub fn bytes_mut(&mut self) -> &mut BytesMut {
-        &mut self.bytes
+    /// Creates a `LinkedBytes` sized for a header of `header_cap` bytes followed by
+    /// `chunk_count` inserted chunks, so building that layout doesn't reallocate.
+    pub fn with_layout(header_cap: usize, chunk_count: usize) -> Self {
+        let mut this = Self::with_capacity(header_cap);
+        // an insert may also split the current bytes_mut into a node, and the io slices need one
+        // more for the bytes_mut itself
+        let nodes = chunk_count.saturating_mul(2);
+        this.list.reserve(nodes);
+        #[cfg(not(feature = "safe-only"))]
+        this.ioslice.reserve(nodes.saturating_add(1));
+        this
     }
 
+    /// Creates an empty `LinkedBytes` without allocating, the buffers are allocated on the first
+    /// write.
+    ///
+    /// This makes idle instances (e.g. created eagerly per connection) cheap.
     #[inline]
-    pub fn reserve(&mut self, additional: usize) {
-        self.bytes.reserve(additional);
+    pub fn empty() -> Self {
+        Self::from_parts(VecDeque::new(), BytesMut::new())
     }
 
-    pub fn insert(&mut self, bytes: Bytes) {
-        let node = Node::Bytes(bytes);
-        // split current bytes
-        let prev = self.bytes.split();
-
-        self.list.push_back(Node::BytesMut(prev));
-        self.list.push_back(node);
+    /// Creates a `LinkedBytes` from static parts without copying, with an empty bytes_mut.
+    pub fn from_static_parts<I: IntoIterator<Item = &'static [u8]>>(parts: I) -> Self {
+        let mut this = Self::new();
+        for part in parts.into_iter().filter(|part| !part.is_empty()) {
+            this.push_back(Node::Static(part));
+        }
+        this
     }
 
-    pub fn insert_faststr(&mut self, fast_str: FastStr) {
-        let node = Node::FastStr(fast_str);
-        // split current bytes
-        let prev = self.bytes.split();
-
-        self.list.push_back(Node::BytesMut(prev));
-        self.list.push_back(node);
+    /// Creates a `LinkedBytes` which adds the number of inserted bytes to `counter`, and
+    /// subtracts them back as they are removed (e.g. by [`advance`](Buf::advance) or
+    /// `consume_front_nodes`), and on `reset`, `clear` or drop.
+    ///
+    /// Every byte added by a method of `LinkedBytes` counts as inserted: the inserts and
+    /// prepends, the reserved slots, varints and placeholders, the replaced nodes, and the merged
+    /// or cloned content. The bytes written through [`BufMut`] don't, which keeps the counter off
+    /// the hot path. A removal releases the removed zero-copy bytes, and anyway down to the
+    /// remaining length.
+    ///
+    /// This lets a flow-control layer cap the total bytes buffered across many instances.
+    pub fn with_accounting(counter: Arc<AtomicUsize>) -> Self {
+        let mut this = Self::new();
+        this.accounting = Some(counter);
+        this
     }
 
-    // TODO: use write_all_vectored when stable
-    pub async fn write_all_vectored<W: AsyncWrite + Unpin>(
-        &mut self,
-        writer: &mut W,
-    ) -> std::io::Result<()> {
-        assert!(
-            self.ioslice.is_empty(),
-            "ioslice must be empty, maybe forget to call `reset`"
-        );
-        self.ioslice.reserve(self.list.len() + 1);
-        // prepare ioslice
-        for node in self.list.iter() {
-            let bytes = node.as_ref();
-            if bytes.is_empty() {
-                continue;
-            }
-            // SAFETY: we can guarantee that the lifetime of `bytes` can't outlive self
-            self.ioslice
-                .push(IoSlice::new(unsafe { &*(bytes as *const _) }));
-        }
-        self.ioslice
-            .push(IoSlice::new(unsafe { &*(self.bytes.as_ref() as *const _) }));
-
-        // do write_all_vectored
-        // we use usize here to avoid `Send` bound required for *mut IoSlice
-        let (mut base_ptr, mut len) = (self.ioslice.as_mut_ptr() as usize, self.ioslice.len());
-        while len != 0 {
-            let ioslice = unsafe { std::slice::from_raw_parts(base_ptr as *mut IoSlice, len) };
-            let n = writer.write_vectored(ioslice).await?;
-            if n == 0 {
-                return Err(std::io::ErrorKind::WriteZero.into());
-            }
-            // Number of buffers to remove.
-            let mut remove = 0;
-            // Total length of all the to be removed buffers.
-            let mut accumulated_len = 0;
-            for buf in ioslice.iter() {
-                if accumulated_len + buf.len() > n {
-                    break;
-                } else {
-                    accumulated_len += buf.len();
-                    remove += 1;
-                }
-            }
-
-            // adjust the outer [IoSlice]
-            base_ptr = unsafe { (base_ptr as *mut IoSlice).add(remove) as usize };
-            len -= remove;
-            if len == 0 {
-                assert!(
-                    n == accumulated_len,
-                    "advancing io slices beyond their length"
-                );
+    /// Creates a `LinkedBytes` from the content of a [`Buf`].
+    ///
+    /// Large chunks are taken by [`Buf::copy_to_bytes`], which doesn't copy for sources backed by
+    /// `Bytes` (e.g. `Bytes` itself, or a `Chain` of them), and are inserted as nodes. Small chunks
+    /// are copied into the current bytes_mut.
+    pub fn from_buf(mut buf: impl Buf) -> Self {
+        let mut this = Self::new();
+        while buf.has_remaining() {
+            let len = buf.chunk().len();
+            if len >= MIN_ZERO_COPY_LEN {
+                let bytes = buf.copy_to_bytes(len);
+                this.insert(bytes);
             } else {
-                // adjust the inner IoSlice
-                let inner_slice = unsafe { &mut *(base_ptr as *mut IoSlice) };
-                let (inner_ptr, inner_len) = (inner_slice.as_ptr(), inner_slice.len());
-                let remaining = n - accumulated_len;
-                assert!(
-                    remaining <= inner_len,
-                    "advancing io slice beyond its length"
-                );
-                let new_ptr = unsafe { inner_ptr.add(remaining) };
-                let new_len = inner_len - remaining;
-                *inner_slice =
-                    IoSlice::new(unsafe { std::slice::from_raw_parts(new_ptr, new_len) });
+                this.bytes.extend_from_slice(buf.chunk());
+                buf.advance(len);
             }
         }
-        self.ioslice.clear();
-        Ok(())
+        this
     }
 
-    // TODO: use write_all_vectored when stable
-    pub fn sync_write_all_vectored<W: std::io::Write>(
-        &mut self,
-        writer: &mut W,
-    ) -> std::io::Result<()> {
-        assert!(
-            self.ioslice.is_empty(),
-            "ioslice must be empty, maybe forget to call `reset`"
-        );
-        self.ioslice.reserve(self.list.len() + 1);
-        // prepare ioslice
-        for node in self.list.iter() {
-            let bytes = node.as_ref();
-            if bytes.is_empty() {
-                continue;
-            }
-            // SAFETY: we can guarantee that the lifetime of `bytes` can't outlive self
-            self.ioslice
-                .push(IoSlice::new(unsafe { &*(bytes as *const _) }));
+    /// Rebuilds a `LinkedBytes` from the list and the current bytes_mut, see
+    /// [`into_parts`](Self::into_parts).
+    ///
+    /// `reset` reclaims the `BytesMut` nodes still contiguous with `bytes` in memory, e.g. those
+    /// split from it as `into_parts` returns them.
+    pub fn from_parts(mut list: VecDeque<Node>, bytes: BytesMut) -> Self {
+        list.retain(|node| matches!(node, Node::BytesMut(_)) || !node.as_ref().is_empty());
+        let list_len = list
+            .iter()
+            .fold(0, |total, node| add_len(total, node.as_ref().len()));
+        Self {
+            // reserved by `prepare_ioslice` on demand
+            #[cfg(not(feature = "safe-only"))]
+            ioslice: Vec::new(),
+            bytes,
+            list,
+            list_len,
+            list_len_dirty: false,
+            front_count: 0,
+            front_epoch: 0,
+            slots: Vec::new(),
+            next_slot_id: 0,
+            node_pool: Vec::new(),
+            node_pool_size: 0,
+            frame_start: 0,
+            contiguous: false,
+            writev_strategy: WritevStrategy::DEFAULT,
+            carry_over_capacity: None,
+            carried_over: 0,
+            size_limit: None,
+            accounting: None,
+            accounted: 0,
+            #[cfg(not(feature = "safe-only"))]
+            io_slice_cache: Vec::new(),
+            #[cfg(not(feature = "safe-only"))]
+            io_slice_cache_key: None,
+            generation: 0,
+            #[cfg(not(feature = "safe-only"))]
+            sealed_tail: None,
+            deque_shrink_threshold: DEFAULT_DEQUE_SHRINK_THRESHOLD,
         }
-        self.ioslice
-            .push(IoSlice::new(unsafe { &*(self.bytes.as_ref() as *const _) }));
+    }
 
-        // do write_all_vectored
-        let (mut base_ptr, mut len) = (self.ioslice.as_mut_ptr(), self.ioslice.len());
-        while len != 0 {
-            let ioslice = unsafe { std::slice::from_raw_parts(base_ptr, len) };
-            let n = writer.write_vectored(ioslice)?;
-            if n == 0 {
-                return Err(std::io::ErrorKind::WriteZero.into());
-            }
-            // Number of buffers to remove.
-            let mut remove = 0;
-            // Total length of all the to be removed buffers.
-            let mut accumulated_len = 0;
-            for buf in ioslice.iter() {
-                if accumulated_len + buf.len() > n {
-                    break;
-                } else {
-                    accumulated_len += buf.len();
-                    remove += 1;
-                }
-            }
+    /// Consumes the `LinkedBytes`, returning the list and the current bytes_mut.
+    ///
+    /// The pooled nodes and the io slices prepared for writing are dropped.
+    pub fn into_parts(mut self) -> (VecDeque<Node>, BytesMut) {
+        // ioslice points to the list and bytes, clear it first
+        self.invalidate_io_slices();
+        let list = std::mem::take(&mut self.list);
+        let bytes = std::mem::take(&mut self.bytes);
+        (list, bytes)
+    }
 
-            // adjust the outer [IoSlice]
-            base_ptr = unsafe { (base_ptr as *mut IoSlice).add(remove) };
-            len -= remove;
-            if len == 0 {
-                assert!(
-                    n == accumulated_len,
-                    "advancing io slices beyond their length"
-                );
-            } else {
-                // adjust the inner IoSlice
-                let inner_slice = unsafe { &mut *(base_ptr as *mut IoSlice) };
-                let (inner_ptr, inner_len) = (inner_slice.as_ptr(), inner_slice.len());
-                let remaining = n - accumulated_len;
-                assert!(
-                    remaining <= inner_len,
-                    "advancing io slice beyond its length"
-                );
-                let new_ptr = unsafe { inner_ptr.add(remaining) };
-                let new_len = inner_len - remaining;
-                *inner_slice =
-                    IoSlice::new(unsafe { std::slice::from_raw_parts(new_ptr, new_len) });
+    /// Moves the whole content to the end of `dst` without copying, consuming `self`.
+    ///
+    /// The nodes are moved as they are, and the current bytes_mut becomes a node of `dst`.
+    pub fn merge_into(self, dst: &mut LinkedBytes) {
+        let len = self.len();
+        let (list, bytes) = self.into_parts();
+        if len == 0 {
+            return;
+        }
+        dst.account(len);
+        if dst.contiguous {
+            dst.bytes.reserve(len);
+            for chunk in chunks(&list, &bytes) {
+                dst.bytes.extend_from_slice(chunk);
             }
+            return;
         }
-        self.ioslice.clear();
-        Ok(())
+        // one more for the split bytes_mut and one for `bytes`
+        dst.list.reserve(list.len() + 2);
+        dst.split_before_insert();
+        for node in list {
+            dst.push_back(node);
+        }
+        if !bytes.is_empty() {
+            dst.push_back(Node::BytesMut(bytes));
+        }
+        dst.debug_assert_no_internal_empties();
     }
 
-    pub fn reset(&mut self) {
-        // ioslice must be cleared before list
-        self.ioslice.clear();
+    /// Appends the whole content of `other` without copying, the counterpart of
+    /// [`merge_into`](Self::merge_into) for call sites owning the destination.
+    #[inline]
+    pub fn extend_from_linked(&mut self, other: LinkedBytes) {
+        other.merge_into(self);
+    }
 
-        if self.list.is_empty() {
-            // only clear bytes
-            self.bytes.clear();
-            return;
-        }
+    /// Returns the total number of bytes in the list and the current bytes_mut.
+    ///
+    /// This is O(1), the length of the list is cached and updated on every insertion.
+    /// The cache is overflow-aware: it panics on overflow in debug builds and saturates at
+    /// `usize::MAX` in release builds. Use [`try_len`](Self::try_len) if you need to detect it.
+    #[inline]
+    pub fn len(&self) -> usize {
+        add_len(self.list_len(), self.bytes.len())
+    }
 
-        let Node::BytesMut(mut head) = self.list.pop_front().unwrap() else {
-            // this should not happen
-            panic!("head is not BytesMut");
-        };
+    /// Returns `true` if there is no byte in the list and the current bytes_mut.
+    ///
+    /// Like [`len`](Self::len), this is O(1) and checks the cached length of the list.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list_len() == 0 && self.bytes.is_empty()
+    }
 
-        while let Some(node) = self.list.pop_front() {
-            if let Node::BytesMut(next_buf) = node {
-                head.unsplit(next_buf);
-            }
+    /// Returns the total length of the nodes in the list, which is walked if a node may have
+    /// been resized through `get_list_mut`.
+    #[inline]
+    fn list_len(&self) -> usize {
+        if self.list_len_dirty {
+            self.list
+                .iter()
+                .map(|node| node.as_ref().len())
+                .fold(0, add_len)
+        } else {
+            self.list_len
         }
+    }
 
-        // don't forget to unsplit self.bytes
-        // here we need to do this in a tricky way, because we can't move self.bytes
-        unsafe {
-            self.bytes.set_len(self.bytes.capacity());
+    /// Recomputes the cached length of the list if a node may have been resized through
+    /// `get_list_mut`.
+    #[inline]
+    fn sync_list_len(&mut self) {
+        if self.list_len_dirty {
+            self.list_len = self.list_len();
+            self.list_len_dirty = false;
         }
-        let remaining = self.bytes.split();
-        head.unsplit(remaining);
-        self.bytes = head;
+    }
 
-        self.bytes.clear();
+    /// Returns the total number of bytes, or `None` if it overflows `usize`.
+    ///
+    /// Unlike [`len`](Self::len), this walks the whole list with checked arithmetic.
+    pub fn try_len(&self) -> Option<usize> {
+        checked_sum_len(
+            self.list
+                .iter()
+                .map(|node| node.as_ref().len())
+                .chain(std::iter::once(self.bytes.len())),
+        )
     }
-}
 
-// Unstable APIs
-impl LinkedBytes {
-    /// This splits the current bytes_mut and push it to the list.
-    /// This is an unstable API that may change in the future, don't rely on this.
-    /// Returns the index of the node.
-    #[doc(hidden)]
     #[inline]
-    pub fn split(&mut self) -> usize {
-        let prev = self.bytes.split();
-        let node = Node::BytesMut(prev);
-        self.list.push_back(node);
-        self.list.len() - 1
+    pub fn bytes(&self) -> &BytesMut {
+        &self.bytes
     }
 
-    /// This gets the node at the given index.
-    /// If you want to get the current bytes_mut, use `bytes_mut()` instead.
-    /// This is an unstable API that may change in the future, don't rely on this.
-    #[doc(hidden)]
+    /// Returns the current bytes_mut to write into directly.
+    ///
+    /// Only the length of the list is cached, the length of the bytes_mut is read on every
+    /// [`len`](Self::len), so it may be changed freely through the returned reference.
     #[inline]
-    pub fn get_list_mut(&mut self, index: usize) -> Option<&mut Node> {
-        self.list.get_mut(index)
+    pub fn bytes_mut(&mut self) -> &mut BytesMut {
+        &mut self.bytes
     }
 
-    /// This gets the iterator of the list.
-    /// This is an unstable API that may change in the future, don't rely on this.
-    #[doc(hidden)]
+    /// Returns the capacity actually allocated for a buffer requested with `len` bytes.
+    ///
+    /// Capacities above 4KB are rounded up to a multiple of 4KB to reduce fragmentation, smaller
+    /// ones are kept as is.
     #[inline]
-    pub fn iter_list(&self) -> impl Iterator<Item = &Node> {
-        self.list.iter()
+    pub const fn recommended_capacity(len: usize) -> usize {
+        if len <= PAGE_SIZE {
+            return len;
+        }
+        match len.checked_add(PAGE_SIZE - 1) {
+            Some(len) => len & !(PAGE_SIZE - 1),
+            None => len,
+        }
     }
-}
 
-impl Default for LinkedBytes {
+    /// Same as [`bytes_mut`](Self::bytes_mut), but ensures at least `min_capacity` bytes of
+    /// spare capacity first, which may be none right after an `insert`.
     #[inline]
-    fn default() -> Self {
-        Self::new()
+    pub fn bytes_mut_with(&mut self, min_capacity: usize) -> &mut BytesMut {
+        self.bytes.reserve(min_capacity);
+        &mut self.bytes
     }
-}
 
-unsafe impl BufMut for LinkedBytes {
+    /// Returns the capacity of the current bytes_mut.
+    ///
+    /// After [`reset`](Self::reset), this is the capacity of the largest buffer it has reclaimed,
+    /// see [`ResetReport::reclaimed_bytes`].
     #[inline]
-    fn remaining_mut(&self) -> usize {
-        self.bytes.remaining_mut()
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
     }
 
+    /// Returns the spare capacity of the current bytes_mut, that is how many bytes can be
+    /// written through [`chunk_mut`](BufMut::chunk_mut) before it reallocates.
     #[inline]
-    unsafe fn advance_mut(&mut self, cnt: usize) {
-        self.bytes.advance_mut(cnt)
+    pub fn writable_chunk_len(&self) -> usize {
+        self.bytes.capacity() - self.bytes.len()
     }
 
     #[inline]
-    fn chunk_mut(&mut self) -> &mut bytes::buf::UninitSlice {
-        self.bytes.chunk_mut()
+    pub fn reserve(&mut self, additional: usize) {
+        self.bytes.reserve(additional);
+    }
+
+    pub fn insert(&mut self, bytes: Bytes) {
+        if bytes.is_empty() {
+            return;
+        }
+        self.account(bytes.len());
+        if self.contiguous {
+            self.bytes.extend_from_slice(&bytes);
+            return;
+        }
+        let node = Node::Bytes(bytes);
+        self.split_before_insert();
+        self.push_back(node);
+    }
+
+    /// Appends an owned `BytesMut` as a node without copying nor freezing it.
+    ///
+    /// Like the split buffers, the node is reclaimed by `reset` if it's the largest run of
+    /// contiguous `BytesMut`s.
+    pub fn insert_bytes_mut(&mut self, buf: BytesMut) {
+        if buf.is_empty() {
+            return;
+        }
+        self.account(buf.len());
+        if self.contiguous {
+            self.bytes.extend_from_slice(&buf);
+            return;
+        }
+        self.split_before_insert();
+        self.push_back(Node::BytesMut(buf));
+    }
+
+    pub fn insert_faststr(&mut self, fast_str: FastStr) {
+        if fast_str.is_empty() {
+            return;
+        }
+        self.account(fast_str.len());
+        if self.contiguous {
+            self.bytes.extend_from_slice(fast_str.as_bytes());
+            return;
+        }
+        let node = Node::FastStr(fast_str);
+        self.split_before_insert();
+        self.push_back(node);
+    }
+
+    pub fn insert_static(&mut self, data: &'static [u8]) {
+        if data.is_empty() {
+            return;
+        }
+        self.account(data.len());
+        if self.contiguous {
+            self.bytes.extend_from_slice(data);
+            return;
+        }
+        let node = Node::Static(data);
+        self.split_before_insert();
+        self.push_back(node);
+    }
+
+    /// Copies the content of the io slices into the current bytes_mut, reserving the total length
+    /// once.
+    pub fn extend_from_io_slices(&mut self, slices: &[IoSlice<'_>]) {
+        let len = slices.iter().map(|slice| slice.len()).fold(0, add_len);
+        self.bytes.reserve(len);
+        for slice in slices {
+            self.bytes.extend_from_slice(slice);
+        }
+    }
+
+    /// Appends the CRC32 of the whole content to the current bytes_mut, as a 4-byte trailer in big
+    /// or little endian. The trailer itself is not covered by the checksum.
+    #[cfg(feature = "checksum")]
+    pub fn append_crc32_trailer(&mut self, big_endian: bool) {
+        let mut hasher = crc32fast::Hasher::new();
+        for chunk in self.iter_chunks() {
+            hasher.update(chunk);
+        }
+        let crc = hasher.finalize();
+        if big_endian {
+            self.bytes.put_u32(crc);
+        } else {
+            self.bytes.put_u32_le(crc);
+        }
+    }
+
+    /// Inserts all the chunks without copying, the current bytes_mut is split only once and the
+    /// deque is reserved up front.
+    pub fn extend_bytes_exact(&mut self, chunks: Vec<Bytes>) {
+        if self.contiguous {
+            for chunk in chunks {
+                self.insert(chunk);
+            }
+            return;
+        }
+        // one more for the split bytes_mut
+        self.list.reserve(chunks.len() + 1);
+        self.split_before_insert();
+        for chunk in chunks.into_iter().filter(|chunk| !chunk.is_empty()) {
+            self.account(chunk.len());
+            self.push_back(Node::Bytes(chunk));
+        }
+    }
+
+    /// Inserts memory managed outside without copying nor taking the ownership, which is useful
+    /// for e.g. a ring buffer that is known to outlive the write.
+    ///
+    /// # Safety
+    ///
+    /// The memory of `data` must stay valid and must not be modified as long as the node is
+    /// alive, that is until it's removed by `reset`, `clear`, `consume_front_nodes` and the like,
+    /// or the `LinkedBytes` (or whichever the node is moved to, e.g. by `split_off_at_chunk` or
+    /// `into_parts`) is dropped. The memory may be read from any thread the `LinkedBytes` is sent
+    /// to.
+    ///
+    /// This is not available with the `safe-only` feature.
+    #[cfg(not(feature = "safe-only"))]
+    pub unsafe fn insert_borrowed_unchecked(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        self.account(data.len());
+        if self.contiguous {
+            self.bytes.extend_from_slice(data);
+            return;
+        }
+        let node = Node::Raw(RawSlice {
+            ptr: data.as_ptr(),
+            len: data.len(),
+        });
+        self.split_before_insert();
+        self.push_back(node);
+    }
+
+    /// Freezes the content written so far into a shared `Bytes` snapshot, and keeps building on
+    /// top of it.
+    ///
+    /// The mutable parts are copied once, then the whole content is replaced by a single `Bytes`
+    /// node sharing the memory with the returned snapshot, and the following writes go to the
+    /// cleared bytes_mut, so they don't affect the snapshot.
+    pub fn snapshot(&mut self) -> Bytes {
+        // ioslice may point to the nodes to be replaced
+        self.invalidate_io_slices();
+        let mut chunks = self.list.iter().filter(|node| !node.as_ref().is_empty());
+        if let (Some(Node::Bytes(b)), None, true) =
+            (chunks.next(), chunks.next(), self.bytes.is_empty())
+        {
+            // already frozen
+            return b.clone();
+        }
+
+        let mut buf = BytesMut::with_capacity(self.len());
+        for chunk in self.iter_chunks() {
+            buf.extend_from_slice(chunk);
+        }
+        let snapshot = buf.freeze();
+
+        self.list.clear();
+        self.list_len = 0;
+        self.clamp_front(0);
+        self.bytes.clear();
+        if !snapshot.is_empty() {
+            self.push_back(Node::Bytes(snapshot.clone()));
+        }
+        self.debug_assert_no_internal_empties();
+        snapshot
+    }
+
+    /// Converts the content into `Bytes` if it can be done without copying, that is there is at
+    /// most one non-empty chunk, otherwise returns `self` back.
+    // returning `self` back by value is the point
+    #[allow(clippy::result_large_err)]
+    pub fn try_freeze_single(mut self) -> Result<Bytes, LinkedBytes> {
+        let mut chunks = self
+            .list
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| !node.as_ref().is_empty());
+        let index = match (chunks.next(), chunks.next(), self.bytes.is_empty()) {
+            (None, _, _) => None,
+            (Some((index, _)), None, true) => Some(index),
+            _ => return Err(self),
+        };
+        // ioslice may point to the nodes to be taken
+        self.invalidate_io_slices();
+        match index.and_then(|index| self.list.remove(index)) {
+            Some(node) => Ok(node.into_bytes()),
+            None => Ok(std::mem::take(&mut self.bytes).freeze()),
+        }
+    }
+
+    /// Converts the whole content into `Bytes`.
+    ///
+    /// Like [`try_freeze_single`](Self::try_freeze_single), this doesn't copy if there is at most
+    /// one non-empty chunk, e.g. a single inserted `Bytes` or only the current bytes_mut. Otherwise
+    /// the chunks are copied into a new buffer.
+    pub fn freeze(self) -> Bytes {
+        match self.try_freeze_single() {
+            Ok(bytes) => bytes,
+            Err(linked) => linked.concat(),
+        }
+    }
+
+    /// Same as [`freeze`](Self::freeze).
+    #[inline]
+    pub fn into_bytes(self) -> Bytes {
+        self.freeze()
+    }
+
+    /// Resets `dst` and copies the content of `self` into it, reusing the allocation of `dst`.
+    ///
+    /// The zero-copy nodes are shared, only the mutable parts are copied.
+    pub fn clone_into(&self, dst: &mut LinkedBytes) {
+        dst.reset();
+        let mutable_len = self
+            .list
+            .iter()
+            .filter_map(|node| match node {
+                Node::BytesMut(_) => Some(node.as_ref().len()),
+                #[cfg(not(feature = "safe-only"))]
+                Node::Raw(_) => Some(node.as_ref().len()),
+                _ => None,
+            })
+            .fold(self.bytes.len(), add_len);
+        dst.reserve(mutable_len);
+        for node in self.list.iter() {
+            match node {
+                Node::Bytes(b) => dst.insert(b.clone()),
+                Node::BytesMut(b) => dst.bytes.extend_from_slice(b),
+                Node::FastStr(s) => dst.insert_faststr(s.clone()),
+                Node::Static(s) => dst.insert_static(s),
+                // the memory may not outlive `dst`
+                #[cfg(not(feature = "safe-only"))]
+                Node::Raw(raw) => dst.bytes.extend_from_slice(raw.as_slice()),
+            }
+        }
+        dst.bytes.extend_from_slice(&self.bytes);
+        // the inserts above account the shared nodes
+        dst.account(mutable_len);
+    }
+
+    /// Converts the content into a `FastStr` without copying if it's held by a single `FastStr`
+    /// node, otherwise returns `None`.
+    pub fn into_faststr(mut self) -> Option<FastStr> {
+        if !self.bytes.is_empty() {
+            return None;
+        }
+        let mut chunks = self
+            .list
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| !node.as_ref().is_empty());
+        let index = match (chunks.next(), chunks.next()) {
+            (Some((index, Node::FastStr(_))), None) => index,
+            _ => return None,
+        };
+        // ioslice may point to the node to be taken
+        self.invalidate_io_slices();
+        match self.list.remove(index) {
+            Some(Node::FastStr(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Flattens the content into a single `BytesMut` of exactly `len()` capacity, which becomes
+    /// the current bytes_mut, and drops all the nodes.
+    ///
+    /// Unlike `reset`, the content is preserved. This is useful to right-size a pooled buffer
+    /// which has grown large.
+    pub fn compact_to_single_allocation(&mut self) {
+        if self.list.is_empty() && self.bytes.capacity() == self.bytes.len() {
+            return;
+        }
+        let mut buf = BytesMut::with_capacity(self.len());
+        self.concat_into(&mut buf);
+
+        // ioslice must be cleared before list
+        self.invalidate_io_slices();
+        self.list.clear();
+        self.list_len = 0;
+        self.clamp_front(0);
+        self.bytes = buf;
+    }
+
+    /// Copies the content into the current bytes_mut if it's not longer than `threshold`, so a
+    /// small message is a single chunk and can be written by one plain `write`. Does nothing
+    /// otherwise.
+    ///
+    /// The memory of the nodes is reclaimed as `reset` does.
+    pub fn flatten_if_small(&mut self, threshold: usize) {
+        if self.len() > threshold || self.list.iter().all(|node| node.as_ref().is_empty()) {
+            return;
+        }
+        let mut content = BytesMut::with_capacity(self.len());
+        self.concat_into(&mut content);
+        // the content is kept, so is its accounting
+        let accounted = self.accounted;
+        self.reset();
+        self.account(accounted);
+        self.bytes.extend_from_slice(&content);
+    }
+
+    /// Sets whether to keep the buffer contiguous.
+    ///
+    /// In contiguous mode, `insert`, `insert_faststr` and `insert_static` copy the data into the
+    /// current bytes_mut instead of creating zero-copy nodes, so the content written after this
+    /// stays in a single chunk. This is for consumers that require a contiguous buffer.
+    #[inline]
+    pub fn set_contiguous_mode(&mut self, contiguous: bool) {
+        self.contiguous = contiguous;
+    }
+
+    /// Seals the current bytes_mut into the list (if it's not empty) and installs `buf` as the new
+    /// bytes_mut, returns the old one for reuse.
+    ///
+    /// The returned `BytesMut` is empty, but keeps the spare capacity of the old bytes_mut.
+    ///
+    /// Note that `buf` is not contiguous with the sealed nodes, so `reset` can't merge them back
+    /// together, and the capacity of the returned buffer is no longer available to `reset`.
+    pub fn swap_trailing_buffer(&mut self, buf: BytesMut) -> BytesMut {
+        self.seal();
+        std::mem::replace(&mut self.bytes, buf)
+    }
+
+    /// Pushes a zero-filled node of `len` bytes to the front of the list, and returns a handle to
+    /// fill it later by [`fill_front_slot`](Self::fill_front_slot).
+    ///
+    /// This is useful to prepend a fixed-size header which can only be computed after the body is
+    /// written.
+    pub fn reserve_front_slot(&mut self, len: usize) -> FrontSlotHandle {
+        self.account(len);
+        let mut slot = self.take_node_buf(len);
+        slot.resize(len, 0);
+        self.push_front(Node::BytesMut(slot));
+        let handle = FrontSlotHandle {
+            seq: self.front_count,
+            len,
+            epoch: self.front_epoch,
+        };
+        self.front_count += 1;
+        handle
+    }
+
+    /// Fills the slot reserved by [`reserve_front_slot`](Self::reserve_front_slot) with `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `data` is not the reserved length, or the handle is invalidated.
+    pub fn fill_front_slot(&mut self, handle: FrontSlotHandle, data: &[u8]) {
+        assert_eq!(
+            data.len(),
+            handle.len,
+            "data length doesn't match the front slot"
+        );
+        let slot = self
+            .front_count
+            .checked_sub(handle.seq + 1)
+            .filter(|_| handle.epoch == self.front_epoch)
+            .and_then(|index| self.list.get_mut(index));
+        match slot {
+            Some(Node::BytesMut(slot)) if slot.len() == handle.len => slot.copy_from_slice(data),
+            _ => panic!("front slot handle is invalidated"),
+        }
+    }
+
+    /// Prepends a static node to the very front, so it leads the chunks and io slices.
+    ///
+    /// This is useful for a fixed preamble which is only known after the body is written, such
+    /// as a magic number.
+    pub fn prepend_static(&mut self, data: &'static [u8]) {
+        if data.is_empty() {
+            return;
+        }
+        self.account(data.len());
+        self.push_front(Node::Static(data));
+        self.front_count += 1;
+    }
+
+    /// Prepends `bytes` to the very front without copying, the current bytes_mut is left
+    /// untouched.
+    ///
+    /// This is for headers only known after the body is written, such as a frame length.
+    pub fn insert_front(&mut self, bytes: Bytes) {
+        if bytes.is_empty() {
+            return;
+        }
+        self.account(bytes.len());
+        self.push_front(Node::Bytes(bytes));
+        self.front_count += 1;
+    }
+
+    /// Same as [`insert_front`](Self::insert_front), but for a `FastStr`.
+    pub fn insert_faststr_front(&mut self, fast_str: FastStr) {
+        if fast_str.is_empty() {
+            return;
+        }
+        self.account(fast_str.len());
+        self.push_front(Node::FastStr(fast_str));
+        self.front_count += 1;
+    }
+
+    /// Prepends the chunks to the very front, keeping their relative order, so the first chunk of
+    /// the iterator leads the output.
+    ///
+    /// This is for encoders producing the output tail-first.
+    pub fn prepend_many(&mut self, chunks: impl IntoIterator<Item = Bytes>) {
+        let chunks: Vec<Bytes> = chunks.into_iter().filter(|b| !b.is_empty()).collect();
+        for chunk in chunks.into_iter().rev() {
+            self.account(chunk.len());
+            self.push_front(Node::Bytes(chunk));
+            self.front_count += 1;
+        }
+    }
+
+    /// Reserves a separate node of at least `n` bytes capacity, and returns a handle to write
+    /// into it.
+    ///
+    /// Unlike [`reserve`](Self::reserve), this doesn't grow the current bytes_mut, so a large
+    /// payload gets its own chunk and allocation. The node is appended when the handle is dropped,
+    /// or copied into the current bytes_mut in contiguous mode.
+    pub fn reserve_as_node(&mut self, n: usize) -> ReservedNode<'_> {
+        let buf = self.take_node_buf(n);
+        ReservedNode { linked: self, buf }
+    }
+
+    /// Appends a dedicated node of `len` zeroed bytes, and returns it to be filled right away.
+    ///
+    /// This is the immediate counterpart of [`reserve_front_slot`](Self::reserve_front_slot),
+    /// for content which can be computed at once. In contiguous mode, the bytes are appended to
+    /// the current bytes_mut instead.
+    pub fn reserve_writable(&mut self, len: usize) -> &mut [u8] {
+        self.account(len);
+        if self.contiguous {
+            let start = self.bytes.len();
+            self.bytes.resize(start + len, 0);
+            return &mut self.bytes[start..];
+        }
+        self.split_before_insert();
+        let mut slot = self.take_node_buf(len);
+        slot.resize(len, 0);
+        self.push_back(Node::BytesMut(slot));
+        match self.list.back_mut() {
+            Some(Node::BytesMut(slot)) => slot,
+            _ => unreachable!("the slot was just pushed"),
+        }
+    }
+
+    /// Reserves a slot for a varint (e.g. a protobuf length prefix) at the current position, and
+    /// returns a handle to fill it later by [`fill_varint_slot`](Self::fill_varint_slot).
+    ///
+    /// The slot is a separate node (even in contiguous mode), so the varint takes exactly as many
+    /// bytes as it needs once the value is known, and the body written after it is left
+    /// untouched.
+    pub fn reserve_varint_slot(&mut self) -> VarintSlotHandle {
+        self.split_before_insert();
+        let handle = VarintSlotHandle {
+            id: self.register_slot(self.list.len()),
+            offset: self.len(),
+        };
+        self.push_back(Node::BytesMut(BytesMut::new()));
+        handle
+    }
+
+    /// Returns the number of bytes written after the varint slot, i.e. the length of the body.
+    #[inline]
+    pub fn varint_slot_body_len(&self, handle: VarintSlotHandle) -> usize {
+        self.len() - handle.offset
+    }
+
+    /// Fills the slot reserved by [`reserve_varint_slot`](Self::reserve_varint_slot) with the
+    /// varint encoding of `value`, which takes 1 to 10 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is invalidated (e.g. by `reset`) or the slot is already filled.
+    pub fn fill_varint_slot(&mut self, handle: VarintSlotHandle, mut value: u64) {
+        let index = self
+            .take_slot(handle.id)
+            .expect("varint slot handle is invalidated");
+        let mut buf = self.take_node_buf(MAX_VARINT_LEN);
+        while value >= 0x80 {
+            buf.put_u8((value as u8) | 0x80);
+            value >>= 7;
+        }
+        buf.put_u8(value as u8);
+        self.account(buf.len());
+        self.list_len = add_len(self.list_len, buf.len());
+        self.generation = self.generation.wrapping_add(1);
+        self.list[index] = Node::BytesMut(buf);
+    }
+
+    /// Inserts an empty placeholder at the current position, and returns a handle to replace it
+    /// later with some [`Bytes`] by [`fill_placeholder`](Self::fill_placeholder), without copying.
+    ///
+    /// Placeholders can be filled in any order, the content written after them is left
+    /// untouched. The placeholder is a separate node, even in contiguous mode.
+    pub fn insert_placeholder(&mut self) -> PlaceholderHandle {
+        self.split_before_insert();
+        let handle = PlaceholderHandle {
+            id: self.register_slot(self.list.len()),
+        };
+        self.push_back(Node::BytesMut(BytesMut::new()));
+        handle
+    }
+
+    /// Replaces the placeholder with `bytes`, without copying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is invalidated or the placeholder is already filled.
+    pub fn fill_placeholder(&mut self, handle: PlaceholderHandle, bytes: Bytes) {
+        let index = self
+            .take_slot(handle.id)
+            .expect("placeholder handle is invalidated");
+        if bytes.is_empty() {
+            return;
+        }
+        self.account(bytes.len());
+        self.list_len = add_len(self.list_len, bytes.len());
+        self.generation = self.generation.wrapping_add(1);
+        self.list[index] = Node::Bytes(bytes);
+    }
+
+    /// Removes the first `count` nodes (e.g. those already flushed) from the list and frees them,
+    /// the current bytes_mut is left untouched.
+    ///
+    /// If `count` is greater than the number of nodes, the whole list is removed.
+    ///
+    /// The memory of the removed nodes is freed (or kept by the node pool), so a later `reset`
+    /// can't reclaim it.
+    pub fn consume_front_nodes(&mut self, count: usize) {
+        let shared = self.pop_front_nodes(count);
+        self.release_removed(shared);
+        self.debug_assert_no_internal_empties();
+    }
+
+    /// Removes the first `count` nodes as `consume_front_nodes` does, without releasing their
+    /// accounting, returns the length of the removed zero-copy nodes.
+    fn pop_front_nodes(&mut self, count: usize) -> usize {
+        // ioslice may point to the nodes to be removed
+        self.invalidate_io_slices();
+        let count = count.min(self.list.len());
+        if count > 0 && self.front_count > 0 {
+            self.front_epoch = self.front_epoch.wrapping_add(1);
+        }
+        self.front_count = self.front_count.saturating_sub(count);
+        self.frame_start = self.frame_start.saturating_sub(count);
+        self.remove_slots(0..count);
+        let mut shared = 0;
+        for _ in 0..count {
+            let node = self.list.pop_front().unwrap();
+            self.list_len = self.list_len.saturating_sub(node.as_ref().len());
+            shared += node.shared_len();
+            self.recycle_node(node);
+        }
+        shared
+    }
+
+    /// Replaces the node at `index` with `new`, returns the old node, or `None` (and drops `new`)
+    /// if `index` is out of bounds.
+    ///
+    /// This is useful to swap a placeholder for the real content once it's computed. Note that
+    /// `reset` only reclaims the `BytesMut` nodes, so replacing one of them with another kind of
+    /// node leaves its memory to be freed instead of reused.
+    pub fn replace_node(&mut self, index: usize, new: Node) -> Option<Node> {
+        // ioslice may point to the node to be replaced
+        self.invalidate_io_slices();
+        // keep the empty nodes `BytesMut`, see `debug_assert_no_internal_empties`
+        let new = if !matches!(new, Node::BytesMut(_)) && new.as_ref().is_empty() {
+            Node::BytesMut(BytesMut::new())
+        } else {
+            new
+        };
+        let node = self.list.get_mut(index)?;
+        // a slot replaced by an empty node must not be filled anymore
+        self.slots.retain(|&(_, slot)| slot != index);
+        self.list_len = add_len(self.list_len - node.as_ref().len(), new.as_ref().len());
+        self.account(new.as_ref().len());
+        let old = std::mem::replace(&mut self.list[index], new);
+        self.release_removed(old.shared_len());
+        Some(old)
+    }
+
+    /// Removes the first `n` bytes, splitting the node they end in.
+    fn drop_front_bytes(&mut self, mut n: usize) {
+        // ioslice may point to the nodes to be removed
+        self.invalidate_io_slices();
+        let mut count = 0;
+        let mut shared = 0;
+        for node in self.list.iter_mut() {
+            let len = node.as_ref().len();
+            if len > n {
+                let rest = node.split_off(n);
+                shared = node.shared_len();
+                *node = rest;
+                self.list_len -= n;
+                n = 0;
+                break;
+            }
+            n -= len;
+            count += 1;
+        }
+        shared += self.pop_front_nodes(count);
+        let _ = self.bytes.split_to(n.min(self.bytes.len()));
+        self.release_removed(shared);
+        self.debug_assert_no_internal_empties();
+    }
+
+    /// Drops all the nodes and clears the current bytes_mut.
+    ///
+    /// Unlike `reset`, this doesn't try to reclaim the memory of the nodes.
+    pub fn clear(&mut self) {
+        // ioslice must be cleared before list
+        self.invalidate_io_slices();
+        self.release_accounting();
+        while let Some(node) = self.list.pop_front() {
+            self.recycle_node(node);
+        }
+        self.carried_over = 0;
+        self.list_len = 0;
+        self.clamp_front(0);
+        self.bytes.clear();
+    }
+
+    /// Marks the beginning of a frame, the content written before is sealed into the list.
+    pub fn begin_frame(&mut self) {
+        self.seal();
+        self.frame_start = self.list.len();
+    }
+
+    /// Marks the end of the frame started by [`begin_frame`](Self::begin_frame), returns a view
+    /// of the frame's chunks.
+    ///
+    /// To emit repeated frames through one buffer, call [`rotate`](Self::rotate) once a frame is
+    /// written, which reuses its allocations for the next frame and keeps the rest of the list.
+    pub fn end_frame(&mut self) -> FrameView {
+        self.seal();
+        FrameView {
+            start: self.frame_start.min(self.list.len()),
+            end: self.list.len(),
+        }
+    }
+
+    /// Removes the nodes of a written frame, and reclaims their memory for the next frame like
+    /// [`reset`](Self::reset) does, the nodes outside of the frame are kept.
+    ///
+    /// The memory is merged back into the current bytes_mut only if it's empty, e.g. right after
+    /// `end_frame`, otherwise the frame's buffers go to the node pool.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frame is invalidated.
+    pub fn rotate(&mut self, frame: FrameView) {
+        assert!(
+            frame.start <= frame.end && frame.end <= self.list.len(),
+            "the frame is invalidated"
+        );
+        // ioslice may point to the nodes to be removed
+        self.invalidate_io_slices();
+        let count = frame.end - frame.start;
+        if frame.start < self.front_count {
+            self.front_count = frame.start;
+            self.front_epoch = self.front_epoch.wrapping_add(1);
+        }
+        if self.frame_start >= frame.end {
+            self.frame_start -= count;
+        } else {
+            self.frame_start = self.frame_start.min(frame.start);
+        }
+        self.remove_slots(frame.start..frame.end);
+        let mut shared = 0;
+        let mut bufs = Vec::new();
+        for node in self.list.drain(frame.start..frame.end) {
+            self.list_len = self.list_len.saturating_sub(node.as_ref().len());
+            shared += node.shared_len();
+            if let Node::BytesMut(buf) = node {
+                bufs.push(buf);
+            }
+        }
+        // an empty bytes_mut usually directly follows the frame's last buffer
+        let merge_bytes = self.bytes.is_empty();
+        if merge_bytes {
+            bufs.push(std::mem::take(&mut self.bytes));
+        }
+        let mut largest = None;
+        for run in coalesce_runs(bufs) {
+            if merge_bytes {
+                self.keep_larger(&mut largest, Some(run));
+            } else {
+                self.recycle_node(Node::BytesMut(run));
+            }
+        }
+        if let Some(mut buf) = largest {
+            buf.clear();
+            self.bytes = buf;
+        }
+        self.release_removed(shared);
+        self.debug_assert_no_internal_empties();
+    }
+
+    /// Returns an iterator over the non-empty chunks of the frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frame is invalidated.
+    pub fn frame_chunks(&self, frame: FrameView) -> impl Iterator<Item = &[u8]> {
+        self.list
+            .range(frame.start..frame.end)
+            .map(|node| node.as_ref())
+            .filter(|chunk| !chunk.is_empty())
+    }
+
+    /// Returns the non-empty chunks of the frame as io slices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frame is invalidated.
+    pub fn frame_io_slice(&self, frame: FrameView) -> Vec<IoSlice<'_>> {
+        self.frame_chunks(frame).map(IoSlice::new).collect()
+    }
+
+    /// Clamps the front slots and the current frame to the first `count` nodes, after the nodes
+    /// from `count` on are removed.
+    #[inline]
+    fn clamp_front(&mut self, count: usize) {
+        if count < self.front_count {
+            self.front_count = count;
+            self.front_epoch = self.front_epoch.wrapping_add(1);
+        }
+        self.frame_start = self.frame_start.min(count);
+        self.slots.retain(|&(_, index)| index < count);
+    }
+
+    /// Registers a slot to be filled later at `index`, returns its id.
+    #[inline]
+    fn register_slot(&mut self, index: usize) -> u64 {
+        let id = self.next_slot_id;
+        self.next_slot_id += 1;
+        self.slots.push((id, index));
+        id
+    }
+
+    /// Unregisters the slot, returns its current index if it's still an empty node.
+    fn take_slot(&mut self, id: u64) -> Option<usize> {
+        let pos = self.slots.iter().position(|&(slot, _)| slot == id)?;
+        let (_, index) = self.slots.swap_remove(pos);
+        // the node may have been replaced through `get_list_mut` or `replace_node`
+        matches!(self.list.get(index), Some(Node::BytesMut(b)) if b.is_empty()).then_some(index)
+    }
+
+    /// Shifts the slots after the nodes in `removed` are removed, and drops the slots among them.
+    fn remove_slots(&mut self, removed: Range<usize>) {
+        self.slots.retain_mut(|(_, index)| {
+            if removed.contains(index) {
+                return false;
+            }
+            if *index >= removed.end {
+                *index -= removed.len();
+            }
+            true
+        });
+    }
+
+    /// Splits the current bytes_mut into the list if it's not empty.
+    #[inline]
+    fn seal(&mut self) {
+        if !self.bytes.is_empty() {
+            let prev = self.bytes.split();
+            self.push_back(Node::BytesMut(prev));
+            #[cfg(not(feature = "safe-only"))]
+            self.mark_sealed_tail();
+        }
+    }
+
+    /// Records that the last node has just been sealed from the current bytes_mut, see
+    /// `merge_sealed_tail`.
+    #[cfg(not(feature = "safe-only"))]
+    #[inline]
+    fn mark_sealed_tail(&mut self) {
+        // a full bytes_mut is left pointing past the end of the allocation
+        if self.bytes.capacity() > 0 {
+            self.sealed_tail = Some((self.generation, self.bytes.as_ptr() as usize));
+        }
+    }
+
+    /// Sets the tuning of the write path, the default keeps it as is.
+    #[inline]
+    pub fn set_writev_strategy(&mut self, strategy: WritevStrategy) {
+        self.writev_strategy = strategy;
+    }
+
+    /// Sets the length below which the current bytes_mut is copied into the last node on insert,
+    /// instead of being split into a new tiny node, `0` (the default) disables it.
+    ///
+    /// This only applies if the last node is a `BytesMut` with enough spare capacity, e.g. one
+    /// from [`reserve_as_node`](Self::reserve_as_node), which reduces the node count of
+    /// interleaved small writes and inserts.
+    #[inline]
+    pub fn set_merge_trailing_threshold(&mut self, threshold: usize) {
+        self.writev_strategy.merge_small_threshold = threshold;
+    }
+
+    /// Limits the total length to `limit` for the writes through [`BufMut`], `None` (the default)
+    /// for no limit.
+    ///
+    /// Once the limit is reached, [`remaining_mut`](BufMut::remaining_mut) is `0` and
+    /// [`chunk_mut`](BufMut::chunk_mut) returns an empty slice, so generic serializers stop
+    /// there. Writers using `chunk_mut` directly must handle a slice shorter than they need. The
+    /// inserts and the other write paths are not limited.
+    #[inline]
+    pub fn set_size_limit(&mut self, limit: Option<usize>) {
+        self.size_limit = limit;
+    }
+
+    /// Sets the capacity allocated for the current bytes_mut when it's full on insert, so the
+    /// writes following a large insert don't reallocate right away. `None` (the default) carries
+    /// over the capacity of the full buffer, `Some(0)` disables it.
+    #[inline]
+    pub fn set_carry_over_capacity(&mut self, capacity: Option<usize>) {
+        self.carry_over_capacity = capacity;
+    }
+
+    /// Sets the capacity of the deque above which `reset` shrinks it back to the default, so a
+    /// spike of nodes doesn't hold memory forever. Defaults to 4 times the default capacity,
+    /// `usize::MAX` disables it.
+    #[inline]
+    pub fn set_deque_shrink_threshold(&mut self, threshold: usize) {
+        self.deque_shrink_threshold = threshold;
+    }
+
+    /// Sets the max number of freed `BytesMut` nodes kept for reuse, `0` (the default) disables
+    /// the pool.
+    ///
+    /// Nodes freed by [`clear`](Self::clear) and [`consume_front_nodes`](Self::consume_front_nodes)
+    /// are kept in the pool, and reused when a new node buffer is needed (e.g.
+    /// [`reserve_front_slot`](Self::reserve_front_slot)).
+    pub fn set_node_pool_size(&mut self, n: usize) {
+        self.node_pool_size = n;
+        self.node_pool.truncate(n);
+    }
+
+    /// Takes an empty buffer with at least `cap` capacity from the pool, or allocates a new one.
+    fn take_node_buf(&mut self, cap: usize) -> BytesMut {
+        match self.node_pool.iter().position(|buf| buf.capacity() >= cap) {
+            Some(index) => self.node_pool.swap_remove(index),
+            None => BytesMut::with_capacity(Self::recommended_capacity(cap)),
+        }
+    }
+
+    /// Checks the consistency of the internal structure, for fuzzing harnesses to call after
+    /// each operation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any invariant is broken.
+    #[cfg(any(test, feature = "fuzz"))]
+    pub fn verify_invariants(&self) {
+        let list_len = checked_sum_len(self.list.iter().map(|node| node.as_ref().len()));
+        assert_eq!(
+            list_len,
+            Some(self.list_len()),
+            "cached list length is out of sync"
+        );
+        assert_eq!(
+            checked_sum_len(self.iter_chunks().map(<[u8]>::len)),
+            Some(self.len()),
+            "len doesn't match the chunks"
+        );
+        assert!(
+            self.list
+                .iter()
+                .all(|node| matches!(node, Node::BytesMut(_)) || !node.as_ref().is_empty()),
+            "LinkedBytes contains an empty node"
+        );
+        assert!(
+            self.node_pool.len() <= self.node_pool_size,
+            "node pool exceeds its size"
+        );
+        assert!(
+            self.accounting.is_some() || self.accounted == 0,
+            "bytes accounted without a counter"
+        );
+    }
+
+    /// Checks that there is no empty node except the `BytesMut` ones, which are split from the
+    /// current bytes_mut and kept for `reset` to reclaim the memory.
+    #[inline]
+    fn debug_assert_no_internal_empties(&self) {
+        debug_assert!(
+            self.list
+                .iter()
+                .all(|node| matches!(node, Node::BytesMut(_)) || !node.as_ref().is_empty()),
+            "LinkedBytes contains an empty node"
+        );
+    }
+
+    #[inline]
+    fn account(&mut self, len: usize) {
+        if let Some(counter) = &self.accounting {
+            counter.fetch_add(len, Ordering::Relaxed);
+            self.accounted += len;
+        }
+    }
+
+    /// Releases the accounting of the removed content, that is `shared` bytes of zero-copy nodes,
+    /// and anyway down to the remaining length, since the removed `BytesMut` content may have been
+    /// inserted as well (e.g. in contiguous mode).
+    fn release_removed(&mut self, shared: usize) {
+        if let Some(counter) = &self.accounting {
+            let keep = self.accounted.saturating_sub(shared).min(self.len());
+            counter.fetch_sub(self.accounted - keep, Ordering::Relaxed);
+            self.accounted = keep;
+        }
+    }
+
+    #[inline]
+    fn release_accounting(&mut self) {
+        if let Some(counter) = &self.accounting {
+            counter.fetch_sub(self.accounted, Ordering::Relaxed);
+            self.accounted = 0;
+        }
+    }
+
+    /// Keeps the larger one of `largest` and `buf` in `largest` by capacity, and recycles the
+    /// other.
+    fn keep_larger(&mut self, largest: &mut Option<BytesMut>, buf: Option<BytesMut>) {
+        let Some(buf) = buf else {
+            return;
+        };
+        match largest {
+            Some(current) if current.capacity() >= buf.capacity() => {
+                self.recycle_node(Node::BytesMut(buf))
+            }
+            _ => {
+                if let Some(prev) = largest.replace(buf) {
+                    self.recycle_node(Node::BytesMut(prev));
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn recycle_node(&mut self, node: Node) {
+        if let Node::BytesMut(mut buf) = node {
+            if buf.capacity() > 0 && self.node_pool.len() < self.node_pool_size {
+                buf.clear();
+                self.node_pool.push(buf);
+            }
+        }
+    }
+
+    /// Splits the current bytes_mut into the list before inserting a node, or copies it into the
+    /// last node if it's shorter than the `merge_small_threshold` of the strategy and fits.
+    ///
+    /// Nothing is pushed if the current bytes_mut is empty, so consecutive inserts don't leave
+    /// empty nodes between them.
+    fn split_before_insert(&mut self) {
+        let len = self.bytes.len();
+        if len == 0 {
+            return;
+        }
+        // slots and frames refer to the nodes by index, so their nodes are left untouched
+        let mergeable = len < self.writev_strategy.merge_small_threshold
+            && self.list.len() > self.front_count.max(self.frame_start);
+        if mergeable {
+            if let Some(Node::BytesMut(last)) = self.list.back_mut() {
+                if last.capacity() - last.len() >= len {
+                    last.extend_from_slice(&self.bytes);
+                    // keep the allocation for the following writes
+                    self.bytes.clear();
+                    self.list_len = add_len(self.list_len, len);
+                    self.generation = self.generation.wrapping_add(1);
+                    return;
+                }
+            }
+        }
+        let cap = self.bytes.capacity();
+        let prev = self.bytes.split();
+        self.push_back(Node::BytesMut(prev));
+        if self.bytes.capacity() == 0 {
+            // the split buffer was full, reallocate now rather than on the next small write
+            let cap = self.carry_over_capacity.unwrap_or(cap);
+            if cap > 0 {
+                self.bytes.reserve(cap);
+                self.carried_over += 1;
+            }
+        }
+    }
+
+    #[inline]
+    fn push_back(&mut self, node: Node) {
+        self.generation = self.generation.wrapping_add(1);
+        self.list_len = add_len(self.list_len, node.as_ref().len());
+        self.list.push_back(node);
+    }
+
+    #[inline]
+    fn push_front(&mut self, node: Node) {
+        self.generation = self.generation.wrapping_add(1);
+        for (_, index) in self.slots.iter_mut() {
+            *index += 1;
+        }
+        self.list_len = add_len(self.list_len, node.as_ref().len());
+        self.list.push_front(node);
+    }
+
+    // TODO: use write_all_vectored when stable
+    pub async fn write_all_vectored<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        in_write_span("write_all_vectored", async move {
+            // only the current bytes_mut, no need for the io slices
+            if self.list.is_empty() {
+                return write_all_chunk(writer, &self.bytes, "write_all_vectored").await;
+            }
+            if self.len() <= self.writev_strategy.contiguous_fallback_threshold {
+                return write_all_chunk(writer, &self.concat(), "write_all_vectored").await;
+            }
+            self.write_all_vectored_inner(writer, |_| {}, None)
+                .await
+                .map_err(WriteError::into_inner)
+        })
+        .await
+    }
+
+    /// Same as [`write_all_vectored`](Self::write_all_vectored), but the returned error also
+    /// records how many bytes have been written and at which chunk the write failed.
+    pub async fn write_all_vectored_detailed<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<(), WriteError> {
+        in_write_span(
+            "write_all_vectored_detailed",
+            self.write_all_vectored_inner(writer, |_| {}, None),
+        )
+        .await
+    }
+
+    /// Same as [`write_all_vectored`](Self::write_all_vectored), but takes the writer as a trait
+    /// object, so only one copy of the write loop is compiled for all the writer types.
+    pub async fn write_all_vectored_dyn(
+        &mut self,
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> std::io::Result<()> {
+        in_write_span(
+            "write_all_vectored_dyn",
+            self.write_all_vectored_inner(writer, |_| {}, None),
+        )
+        .await
+        .map_err(WriteError::into_inner)
+    }
+
+    /// Same as [`write_all_vectored`](Self::write_all_vectored), but calls `on_progress` with the
+    /// total number of bytes written so far after each successful `write_vectored`.
+    pub async fn write_all_vectored_with_progress<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        on_progress: impl FnMut(usize),
+    ) -> std::io::Result<()> {
+        in_write_span(
+            "write_all_vectored_with_progress",
+            self.write_all_vectored_inner(writer, on_progress, None),
+        )
+        .await
+        .map_err(WriteError::into_inner)
+    }
+
+    /// Same as [`write_all_vectored_detailed`](Self::write_all_vectored_detailed), but gives up
+    /// with a [`TimedOut`](std::io::ErrorKind::TimedOut) error once `deadline` is reached.
+    ///
+    /// On failure, the bytes already written are removed from the buffer, so calling this again
+    /// resumes where it stopped. [`WriteError::bytes_written`] tells how many bytes this call has
+    /// written.
+    pub async fn write_all_vectored_deadline<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        deadline: Instant,
+    ) -> Result<(), WriteError> {
+        let result = in_write_span(
+            "write_all_vectored_deadline",
+            self.write_all_vectored_inner(writer, |_| {}, Some(deadline)),
+        )
+        .await;
+        if let Err(e) = &result {
+            self.drop_front_bytes(e.bytes_written());
+        }
+        result
+    }
+
+    #[cfg(not(feature = "safe-only"))]
+    async fn write_all_vectored_inner<W: AsyncWrite + Unpin + ?Sized>(
+        &mut self,
+        writer: &mut W,
+        mut on_progress: impl FnMut(usize),
+        deadline: Option<Instant>,
+    ) -> Result<(), WriteError> {
+        self.prepare_ioslice();
+        let strategy = self.writev_strategy;
+        let total = self.ioslice.len();
+        let mut written = 0;
+        let mut syscalls = 0;
+
+        // do write_all_vectored
+        // the pointer is held across `.await`, so wrap it to keep the future `Send`
+        let (mut base_ptr, mut len) = (SendPtr(self.ioslice.as_mut_ptr()), self.ioslice.len());
+        while len != 0 {
+            // only the first `window` io slices are handed to the writer
+            let window = strategy.window(unsafe { std::slice::from_raw_parts(base_ptr.0, len) });
+            let ioslice = unsafe { std::slice::from_raw_parts(base_ptr.0, window) };
+            let write = writer.write_vectored(ioslice);
+            let result = match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, write).await {
+                    Ok(result) => result,
+                    Err(_) => Err(std::io::ErrorKind::TimedOut.into()),
+                },
+                None => write.await,
+            };
+            syscalls += 1;
+            let n = match result {
+                Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+                result => result,
+            };
+            let n = match n {
+                Ok(n) => n,
+                Err(e) => {
+                    // the io slices may split a chunk, so count in chunks
+                    let e = WriteError::new(e, written, self.chunks_covered_by(written).0);
+                    trace_write("write_all_vectored", total, written, syscalls, Some(&e));
+                    return Err(e);
+                }
+            };
+            written += n;
+            on_progress(written);
+            let (new_ptr, new_window) = unsafe { advance_ioslices(base_ptr.0, window, n) };
+            (base_ptr, len) = (SendPtr(new_ptr), new_window + (len - window));
+        }
+        self.ioslice.clear();
+        trace_write("write_all_vectored", total, written, syscalls, None);
+        Ok(())
+    }
+
+    /// Writes the whole content to both `w1` and `w2`, one after the other.
+    ///
+    /// The io slices are built locally for each writer, so this takes `&self` and doesn't need a
+    /// `reset` afterwards.
+    pub async fn write_all_vectored_tee<W1, W2>(
+        &self,
+        w1: &mut W1,
+        w2: &mut W2,
+    ) -> std::io::Result<()>
+    where
+        W1: AsyncWrite + Unpin,
+        W2: AsyncWrite + Unpin,
+    {
+        in_write_span("write_all_vectored_tee", async move {
+            let method = "write_all_vectored_tee";
+            write_all_io_slices(w1, self.io_slice(), self.writev_strategy, method).await?;
+            write_all_io_slices(w2, self.io_slice(), self.writev_strategy, method).await
+        })
+        .await
+    }
+
+    /// Writes the first `n` bytes to `writer` and removes them, the rest stays buffered.
+    ///
+    /// The io slices are only built for the prefix. If the write fails, nothing is removed,
+    /// though part of the prefix may have been written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > len`.
+    pub async fn write_prefix<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        n: usize,
+    ) -> std::io::Result<()> {
+        assert!(
+            n <= self.len(),
+            "write_prefix out of bounds: {:?} <= {:?}",
+            n,
+            self.len()
+        );
+        let mut remaining = n;
+        let slices = chunks(&self.list, &self.bytes)
+            .map_while(|chunk| {
+                if remaining == 0 {
+                    return None;
+                }
+                let len = chunk.len().min(remaining);
+                remaining -= len;
+                Some(&chunk[..len])
+            })
+            .flat_map(|chunk| chunk.chunks(MAX_IOSLICE_LEN))
+            .map(IoSlice::new)
+            .collect();
+        let write = write_all_io_slices(writer, slices, self.writev_strategy, "write_prefix");
+        in_write_span("write_prefix", write).await?;
+        self.drop_front_bytes(n);
+        Ok(())
+    }
+
+    // TODO: use write_all_vectored when stable
+    pub fn sync_write_all_vectored<W: std::io::Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        #[cfg(feature = "tracing")]
+        let _span = write_span("sync_write_all_vectored").entered();
+        // only the current bytes_mut, no need for the io slices
+        if self.list.is_empty() {
+            return sync_write_all_chunk(writer, &self.bytes, "sync_write_all_vectored");
+        }
+        if self.len() <= self.writev_strategy.contiguous_fallback_threshold {
+            return sync_write_all_chunk(writer, &self.concat(), "sync_write_all_vectored");
+        }
+        self.sync_write_all_vectored_inner(writer)
+            .map_err(WriteError::into_inner)
+    }
+
+    /// Same as [`sync_write_all_vectored`](Self::sync_write_all_vectored), but the returned error
+    /// also records how many bytes have been written and at which chunk the write failed.
+    #[inline]
+    pub fn sync_write_all_vectored_detailed<W: std::io::Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<(), WriteError> {
+        #[cfg(feature = "tracing")]
+        let _span = write_span("sync_write_all_vectored_detailed").entered();
+        self.sync_write_all_vectored_inner(writer)
+    }
+
+    #[cfg(not(feature = "safe-only"))]
+    fn sync_write_all_vectored_inner<W: std::io::Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<(), WriteError> {
+        self.prepare_ioslice();
+        let strategy = self.writev_strategy;
+        let total = self.ioslice.len();
+        let mut written = 0;
+        let mut syscalls = 0;
+
+        // do write_all_vectored
+        let (mut base_ptr, mut len) = (self.ioslice.as_mut_ptr(), self.ioslice.len());
+        while len != 0 {
+            // only the first `window` io slices are handed to the writer
+            let window = strategy.window(unsafe { std::slice::from_raw_parts(base_ptr, len) });
+            let ioslice = unsafe { std::slice::from_raw_parts(base_ptr, window) };
+            let result = writer.write_vectored(ioslice);
+            syscalls += 1;
+            let n = match result {
+                Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+                result => result,
+            };
+            let n = match n {
+                Ok(n) => n,
+                // retried as `Write::write_all` does
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    // the io slices may split a chunk, so count in chunks
+                    let e = WriteError::new(e, written, self.chunks_covered_by(written).0);
+                    trace_write(
+                        "sync_write_all_vectored",
+                        total,
+                        written,
+                        syscalls,
+                        Some(&e),
+                    );
+                    return Err(e);
+                }
+            };
+            written += n;
+            let (new_ptr, new_window) = unsafe { advance_ioslices(base_ptr, window, n) };
+            (base_ptr, len) = (new_ptr, new_window + (len - window));
+        }
+        self.ioslice.clear();
+        trace_write("sync_write_all_vectored", total, written, syscalls, None);
+        Ok(())
+    }
+
+    #[cfg(feature = "safe-only")]
+    async fn write_all_vectored_inner<W: AsyncWrite + Unpin + ?Sized>(
+        &mut self,
+        writer: &mut W,
+        on_progress: impl FnMut(usize),
+        deadline: Option<Instant>,
+    ) -> Result<(), WriteError> {
+        let mut slices = self.io_slice();
+        write_io_slices_safe(
+            writer,
+            &mut slices,
+            self.writev_strategy,
+            on_progress,
+            deadline,
+            "write_all_vectored",
+        )
+        .await
+        .map_err(|e| self.chunk_write_error(e))
+    }
+
+    #[cfg(feature = "safe-only")]
+    fn sync_write_all_vectored_inner<W: std::io::Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<(), WriteError> {
+        let mut slices = self.io_slice();
+        sync_write_io_slices_safe(writer, &mut slices, self.writev_strategy)
+            .map_err(|e| self.chunk_write_error(e))
+    }
+
+    /// Counts the failed io slice of `e` in chunks, since the io slices may split a chunk.
+    #[cfg(feature = "safe-only")]
+    fn chunk_write_error(&self, mut e: WriteError) -> WriteError {
+        e.last_chunk_index = self.chunks_covered_by(e.bytes_written).0;
+        e
+    }
+
+    /// Clears the io slices and invalidates the cached ones, this must be called before changing
+    /// the list.
+    #[inline]
+    fn invalidate_io_slices(&mut self) {
+        self.sync_list_len();
+        #[cfg(not(feature = "safe-only"))]
+        {
+            self.ioslice.clear();
+            self.io_slice_cache.clear();
+            self.io_slice_cache_key = None;
+        }
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    #[cfg(not(feature = "safe-only"))]
+    fn prepare_ioslice(&mut self) {
+        assert!(
+            self.ioslice.is_empty(),
+            "ioslice must be empty, maybe forget to call `reset`"
+        );
+        self.ioslice.reserve(self.list.len() + 1);
+        let merged = self.merge_sealed_tail();
+        // the last node is covered by the merged slice
+        let nodes = self.list.len() - usize::from(merged.is_some());
+        let slices = self
+            .list
+            .range(..nodes)
+            .map(|node| node.as_ref() as *const [u8])
+            .chain(std::iter::once(merged.unwrap_or(&*self.bytes as *const _)))
+            // SAFETY: the merged slice points to the memory of the last node and bytes_mut
+            .map(|chunk| unsafe { &*chunk })
+            .filter(|chunk| !chunk.is_empty())
+            .flat_map(|chunk| chunk.chunks(MAX_IOSLICE_LEN));
+        for bytes in slices {
+            // SAFETY: we can guarantee that the lifetime of `bytes` can't outlive self
+            self.ioslice
+                .push(IoSlice::new(unsafe { &*(bytes as *const _) }));
+        }
+    }
+
+    /// Returns the content of the last node and the current bytes_mut as one slice, if the node
+    /// was sealed from the bytes_mut and neither has changed since, so a buffer written around a
+    /// `begin_frame` or `end_frame` takes one io slice less.
+    ///
+    /// The slice must be derived from a single `BytesMut` to cover both, so the bytes_mut is
+    /// unsplit into the node and split back right away, neither of which copies since they are
+    /// adjacent in the same allocation.
+    #[cfg(not(feature = "safe-only"))]
+    fn merge_sealed_tail(&mut self) -> Option<*const [u8]> {
+        let (generation, ptr) = self.sealed_tail?;
+        // any change of the list bumps the generation, and the bytes_mut can't be moved to the
+        // same pointer since the node keeps the allocation alive
+        if generation != self.generation
+            || ptr != self.bytes.as_ptr() as usize
+            || self.bytes.is_empty()
+        {
+            return None;
+        }
+        let Some(Node::BytesMut(last)) = self.list.back_mut() else {
+            return None;
+        };
+        if last.is_empty() || last.as_ptr().wrapping_add(last.len()) != self.bytes.as_ptr() {
+            return None;
+        }
+        let len = last.len();
+        last.unsplit(self.bytes.split());
+        let merged: *const [u8] = &**last;
+        let mut tail = last.split_off(len);
+        tail.unsplit(std::mem::take(&mut self.bytes));
+        self.bytes = tail;
+        Some(merged)
+    }
+
+    /// Returns an iterator over the chunks, that is the nodes of the list followed by the current
+    /// bytes_mut.
+    ///
+    /// Empty chunks are skipped, which is consistent with [`chunks_count`](Self::chunks_count),
+    /// [`io_slice`](Self::io_slice) and the vectored writes. Structural APIs such as
+    /// [`node_count`](Self::node_count) count every node, including the empty ones.
+    #[inline]
+    pub fn iter_chunks(&self) -> impl Iterator<Item = &[u8]> {
+        chunks(&self.list, &self.bytes)
+    }
+
+    /// Returns the number of non-empty chunks, see [`iter_chunks`](Self::iter_chunks).
+    #[inline]
+    pub fn chunks_count(&self) -> usize {
+        self.iter_chunks().count()
+    }
+
+    /// Returns the non-empty chunks as io slices, see [`iter_chunks`](Self::iter_chunks).
+    ///
+    /// On Windows, a `WSABUF` can't hold more than `u32::MAX` bytes, so larger chunks are split
+    /// into several io slices.
+    #[inline]
+    pub fn io_slice(&self) -> Vec<IoSlice<'_>> {
+        chunks(&self.list, &self.bytes)
+            .flat_map(|chunk| chunk.chunks(MAX_IOSLICE_LEN))
+            .map(IoSlice::new)
+            .collect()
+    }
+
+    /// Same as [`io_slice`](Self::io_slice), but returns at most `max` io slices, along with the
+    /// number of bytes they cover.
+    ///
+    /// This is for writers accepting a bounded number of io slices at once.
+    pub fn io_slice_limited(&self, max: usize) -> (Vec<IoSlice<'_>>, usize) {
+        let slices: Vec<IoSlice<'_>> = chunks(&self.list, &self.bytes)
+            .flat_map(|chunk| chunk.chunks(MAX_IOSLICE_LEN))
+            .take(max)
+            .map(IoSlice::new)
+            .collect();
+        let len = slices.iter().map(|slice| slice.len()).sum();
+        (slices, len)
+    }
+
+    /// Same as [`io_slice`](Self::io_slice), but the io slices are cached and only rebuilt after
+    /// the content has changed, for callers asking for them repeatedly.
+    ///
+    /// The io slices borrow `self`, so they can't outlive the next mutation, and custom vectored
+    /// writes don't allocate once the cache has grown.
+    ///
+    /// The cache is self-referential, so it's not available with the `safe-only` feature.
+    #[cfg(not(feature = "safe-only"))]
+    pub fn io_slice_cached(&mut self) -> &[IoSlice<'_>] {
+        let key = (
+            self.generation,
+            self.bytes.as_ptr() as usize,
+            self.bytes.len(),
+        );
+        if self.io_slice_cache_key != Some(key) {
+            self.io_slice_cache.clear();
+            let slices =
+                chunks(&self.list, &self.bytes).flat_map(|chunk| chunk.chunks(MAX_IOSLICE_LEN));
+            for bytes in slices {
+                // SAFETY: the cache is rebuilt whenever the list or the bytes_mut is changed, and
+                // the returned lifetime can't outlive self
+                self.io_slice_cache
+                    .push(IoSlice::new(unsafe { &*(bytes as *const _) }));
+            }
+            self.io_slice_cache_key = Some(key);
+        }
+        &self.io_slice_cache
+    }
+
+    /// Returns the pointer and length of the content if it's contiguous (at most one non-empty
+    /// chunk), or `None` if it's fragmented, in which case FFI callers should fall back to copying
+    /// the chunks.
+    ///
+    /// The pointer is only valid while `&self` is held, and must not be used after any mutation.
+    /// For an empty buffer, the pointer is dangling and must not be dereferenced.
+    pub fn as_ffi_parts(&self) -> Option<(*const u8, usize)> {
+        let mut chunks = self.iter_chunks();
+        match (chunks.next(), chunks.next()) {
+            (None, _) => Some((self.bytes.as_ptr(), 0)),
+            (Some(chunk), None) => Some((chunk.as_ptr(), chunk.len())),
+            (Some(_), Some(_)) => None,
+        }
+    }
+
+    /// Locates the byte at `offset`, returns the chunk containing it and its index in the chunk,
+    /// or `None` if `offset` is out of bounds.
+    pub fn chunk_at(&self, mut offset: usize) -> Option<(&[u8], usize)> {
+        for chunk in self.iter_chunks() {
+            if offset < chunk.len() {
+                return Some((chunk, offset));
+            }
+            offset -= chunk.len();
+        }
+        None
+    }
+
+    /// Returns how many whole chunks the first `n` bytes cover, and the offset into the next
+    /// chunk, e.g. to advance a cursor after a partial `write_vectored` of `n` bytes.
+    ///
+    /// Chunks are counted as in [`iter_chunks`](Self::iter_chunks). If `n` is greater than the
+    /// length, all the chunks are covered and the offset is `0`.
+    pub fn chunks_covered_by(&self, mut n: usize) -> (usize, usize) {
+        let mut whole = 0;
+        for chunk in self.iter_chunks() {
+            if chunk.len() > n {
+                return (whole, n);
+            }
+            n -= chunk.len();
+            whole += 1;
+        }
+        (whole, 0)
+    }
+
+    /// Folds over the non-empty chunks in order, without allocation.
+    ///
+    /// This is useful to build checksums, hashes or scanners on top of.
+    #[inline]
+    pub fn fold_chunks<B>(&self, init: B, f: impl FnMut(B, &[u8]) -> B) -> B {
+        self.iter_chunks().fold(init, f)
+    }
+
+    /// Counts the occurrences of each byte value across all the chunks in one pass.
+    ///
+    /// This is handy for statistics such as entropy checks without flattening the buffer.
+    pub fn byte_histogram(&self) -> [u64; 256] {
+        let mut histogram = [0u64; 256];
+        for chunk in self.iter_chunks() {
+            for &b in chunk {
+                histogram[b as usize] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Copies the whole content into a new `Bytes` of exactly [`len`](Self::len) bytes.
+    ///
+    /// Use [`freeze`](Self::freeze) to avoid the copy when the buffer is no longer needed.
+    pub fn concat(&self) -> Bytes {
+        let mut out = BytesMut::with_capacity(self.len());
+        self.concat_into(&mut out);
+        out.freeze()
+    }
+
+    /// Appends all the chunks to `out`, reserving the whole length once.
+    ///
+    /// The caller owns the destination, so it can be reused or shared by several `LinkedBytes`.
+    pub fn concat_into(&self, out: &mut BytesMut) {
+        out.reserve(self.len());
+        for chunk in self.iter_chunks() {
+            out.extend_from_slice(chunk);
+        }
+    }
+
+    /// Copies the bytes in `range` out, without flattening the whole buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or `range.start > range.end`.
+    pub fn subslice_copy(&self, range: Range<usize>) -> Vec<u8> {
+        assert!(
+            range.start <= range.end,
+            "range start {} is greater than range end {}",
+            range.start,
+            range.end
+        );
+        assert!(
+            range.end <= self.len(),
+            "range end {} is out of bounds of length {}",
+            range.end,
+            self.len()
+        );
+        let mut out = Vec::with_capacity(range.len());
+        // offset of the current chunk
+        let mut offset = 0;
+        for chunk in self.iter_chunks() {
+            if out.len() == range.len() {
+                break;
+            }
+            let chunk_end = offset + chunk.len();
+            if chunk_end > range.start {
+                let start = range.start.saturating_sub(offset);
+                let end = range.end.min(chunk_end) - offset;
+                out.extend_from_slice(&chunk[start..end]);
+            }
+            offset = chunk_end;
+        }
+        out
+    }
+
+    /// Removes the first `n` bytes and returns them.
+    ///
+    /// This doesn't copy if they are in a single chunk, otherwise they are copied into a new
+    /// `Bytes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > len`.
+    pub fn take_bytes(&mut self, n: usize) -> Bytes {
+        assert!(
+            n <= self.len(),
+            "take_bytes out of bounds: {:?} <= {:?}",
+            n,
+            self.len()
+        );
+        if n == 0 {
+            return Bytes::new();
+        }
+        // ioslice may point to the nodes to be taken
+        self.invalidate_io_slices();
+        match self.list.iter_mut().find(|node| !node.as_ref().is_empty()) {
+            Some(node) if node.as_ref().len() >= n => {
+                let rest = if node.as_ref().len() == n {
+                    // keep the indices of the nodes, see `debug_assert_no_internal_empties`
+                    Node::BytesMut(BytesMut::new())
+                } else {
+                    node.split_off(n)
+                };
+                let head = std::mem::replace(node, rest);
+                self.list_len -= n;
+                self.release_removed(head.shared_len());
+                head.into_bytes()
+            }
+            None => {
+                let head = self.bytes.split_to(n).freeze();
+                self.release_removed(0);
+                head
+            }
+            Some(_) => {
+                let out = Bytes::from(self.subslice_copy(0..n));
+                self.drop_front_bytes(n);
+                out
+            }
+        }
+    }
+
+    /// Splits the buffer into two at the given index, `self` keeps `[0, at)` and the tail
+    /// `[at, len)` is returned as `Bytes`.
+    ///
+    /// This doesn't copy if the tail is a single chunk, otherwise the chunks of the tail are
+    /// copied into a new `Bytes`. A tail split off the current bytes_mut takes its spare capacity
+    /// along, so the bytes_mut is reallocated with the carry-over capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off_bytes(&mut self, at: usize) -> Bytes {
+        assert!(
+            at <= self.len(),
+            "split_off out of bounds: {:?} <= {:?}",
+            at,
+            self.len()
+        );
+        self.sync_list_len();
+        if at >= self.list_len {
+            let cap = self.bytes.capacity();
+            let tail = self.bytes.split_off(at - self.list_len).freeze();
+            // the spare capacity went with the tail, reallocate now rather than on the next small
+            // write
+            let cap = self.carry_over_capacity.unwrap_or(cap);
+            if cap > 0 {
+                self.bytes.reserve(cap);
+                self.carried_over += 1;
+            }
+            self.release_removed(0);
+            return tail;
+        }
+
+        // find the node containing `at`
+        let mut offset = 0;
+        let mut index = 0;
+        for node in self.list.iter() {
+            let len = node.as_ref().len();
+            if offset + len > at {
+                break;
+            }
+            offset += len;
+            index += 1;
+        }
+        // ioslice may point to the nodes to be split off
+        self.invalidate_io_slices();
+        let tail_len = self.len() - at;
+        let mut tail = Vec::with_capacity(self.list.len() - index + 1);
+        if at == offset {
+            // `at` is on a node boundary, don't leave an empty node behind
+            tail.extend(self.list.drain(index..));
+        } else {
+            tail.push(self.list[index].split_off(at - offset));
+            tail.extend(self.list.drain(index + 1..));
+        }
+        tail.push(Node::BytesMut(self.bytes.split()));
+        self.list_len = at;
+        self.clamp_front(self.list.len());
+        self.release_removed(tail.iter().map(Node::shared_len).sum());
+        self.debug_assert_no_internal_empties();
+
+        let mut chunks = tail.into_iter().filter(|node| !node.as_ref().is_empty());
+        let first = chunks.next().unwrap();
+        match chunks.next() {
+            None => first.into_bytes(),
+            Some(second) => {
+                let mut out = BytesMut::with_capacity(tail_len);
+                out.extend_from_slice(first.as_ref());
+                out.extend_from_slice(second.as_ref());
+                for node in chunks {
+                    out.extend_from_slice(node.as_ref());
+                }
+                out.freeze()
+            }
+        }
+    }
+
+    /// Splits the buffer into two at the given chunk boundary, `self` keeps the chunks
+    /// `[0, chunk_index)` and the others are moved to the returned `LinkedBytes`.
+    ///
+    /// Chunks are counted as in [`iter_chunks`](Self::iter_chunks). Whole nodes are moved, so
+    /// nothing is copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_index > chunks_count`.
+    pub fn split_off_at_chunk(&mut self, chunk_index: usize) -> LinkedBytes {
+        let count = self.chunks_count();
+        assert!(
+            chunk_index <= count,
+            "split_off_at_chunk out of bounds: {:?} <= {:?}",
+            chunk_index,
+            count
+        );
+        // ioslice may point to the nodes to be moved
+        self.invalidate_io_slices();
+
+        // find the node of the chunk, `None` if it's the current bytes_mut or out of the list
+        let mut seen = 0;
+        let index = self.list.iter().position(|node| {
+            if !node.as_ref().is_empty() {
+                seen += 1;
+            }
+            seen > chunk_index
+        });
+
+        let mut tail = LinkedBytes::with_capacity(0);
+        let mut shared = 0;
+        if let Some(index) = index {
+            for node in self.list.drain(index..) {
+                self.list_len = self.list_len.saturating_sub(node.as_ref().len());
+                shared += node.shared_len();
+                tail.push_back(node);
+            }
+            self.clamp_front(index);
+        }
+        if chunk_index < count {
+            tail.bytes = self.bytes.split();
+        }
+        self.release_removed(shared);
+        tail
+    }
+
+    /// Keeps only the first `count` nodes of the list and frees the others, along with the
+    /// content of the current bytes_mut which comes after them.
+    ///
+    /// Nodes are counted as in [`node_count`](Self::node_count), nothing happens if `count` is not
+    /// less than it. Whole nodes are dropped, so nothing is copied.
+    pub fn truncate_nodes(&mut self, count: usize) {
+        if count >= self.list.len() {
+            return;
+        }
+        // ioslice may point to the nodes to be removed
+        self.invalidate_io_slices();
+        let mut shared = 0;
+        while self.list.len() > count {
+            let node = self.list.pop_back().unwrap();
+            self.list_len = self.list_len.saturating_sub(node.as_ref().len());
+            shared += node.shared_len();
+            self.recycle_node(node);
+        }
+        self.clamp_front(count);
+        self.bytes.clear();
+        self.release_removed(shared);
+        self.debug_assert_no_internal_empties();
+    }
+
+    /// Returns the length of the largest chunk, or `0` if the buffer is empty.
+    #[inline]
+    pub fn max_chunk_len(&self) -> usize {
+        self.iter_chunks().map(<[u8]>::len).max().unwrap_or(0)
+    }
+
+    /// Returns the length of the smallest non-empty chunk, or `0` if the buffer is empty.
+    #[inline]
+    pub fn min_chunk_len(&self) -> usize {
+        self.iter_chunks().map(<[u8]>::len).min().unwrap_or(0)
+    }
+
+    /// Returns the number of non-empty chunks shorter than `threshold` bytes.
+    ///
+    /// This is a cheap hint to decide whether it's worth compacting before a vectored write.
+    #[inline]
+    pub fn count_small_chunks(&self, threshold: usize) -> usize {
+        self.iter_chunks()
+            .filter(|chunk| chunk.len() < threshold)
+            .count()
+    }
+
+    /// Redistributes the content into chunks of `target_chunk` bytes (except the last one), for
+    /// transports that prefer uniform iovec sizes.
+    ///
+    /// Large nodes are split into multiple views without copying, only the bytes of the chunks
+    /// spanning several nodes (e.g. the tiny ones) are copied. The content written after this
+    /// goes to the current bytes_mut as before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target_chunk` is `0`.
+    pub fn rebalance(&mut self, target_chunk: usize) {
+        assert!(target_chunk > 0, "target chunk size must be greater than 0");
+        // ioslice may point to the nodes to be replaced
+        self.invalidate_io_slices();
+        self.clamp_front(0);
+
+        // the content of the current bytes_mut is moved into the list
+        self.list_len = self.len();
+        let tail = Node::BytesMut(self.bytes.split());
+        let nodes: Vec<Node> = self.list.drain(..).chain(std::iter::once(tail)).collect();
+        // the chunk being assembled by copying
+        let mut pending = BytesMut::new();
+        // the length of the content after the current node
+        let mut remaining = self.list_len;
+        for mut node in nodes {
+            remaining -= node.as_ref().len();
+            if node.as_ref().is_empty() {
+                continue;
+            }
+            if !pending.is_empty() {
+                let take = (target_chunk - pending.len()).min(node.as_ref().len());
+                pending.extend_from_slice(&node.as_ref()[..take]);
+                node = node.split_off(take);
+                if pending.len() == target_chunk {
+                    self.list.push_back(Node::BytesMut(pending.split()));
+                }
+            }
+            while node.as_ref().len() >= target_chunk {
+                let rest = node.split_off(target_chunk);
+                self.list.push_back(node);
+                node = rest;
+            }
+            if !node.as_ref().is_empty() {
+                if pending.is_empty() {
+                    // the target may be far larger than the content left
+                    pending.reserve(target_chunk.min(node.as_ref().len() + remaining));
+                }
+                pending.extend_from_slice(node.as_ref());
+            }
+        }
+        if !pending.is_empty() {
+            self.list.push_back(Node::BytesMut(pending));
+        }
+        self.debug_assert_no_internal_empties();
+    }
+
+    /// Returns `true` if all the content is held by zero-copy nodes (`Bytes`, `FastStr`, `Static`
+    /// or `Raw`), that is no byte has been copied into a `BytesMut`.
+    pub fn is_all_zero_copy(&self) -> bool {
+        self.bytes.is_empty()
+            && self
+                .list
+                .iter()
+                .all(|node| !matches!(node, Node::BytesMut(b) if !b.is_empty()))
+    }
+
+    /// Returns the number of non-empty `Bytes` and `FastStr` nodes, whose memory may be shared
+    /// with others and is cheap to keep.
+    pub fn shared_chunk_count(&self) -> usize {
+        self.list
+            .iter()
+            .filter(|node| matches!(node, Node::Bytes(_) | Node::FastStr(_)))
+            .filter(|node| !node.as_ref().is_empty())
+            .count()
+    }
+
+    /// Returns the number of nodes in the list, including the empty ones.
+    ///
+    /// The current bytes_mut is not a node, so it's not counted.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Clears the content, and reclaims the memory split off by `insert` and friends for reuse.
+    ///
+    /// The `BytesMut` nodes still contiguous in memory are merged back, whatever the other nodes
+    /// (e.g. prepended ones) are, and the largest merged buffer is kept for the next writes. If
+    /// the current bytes_mut has been reallocated (e.g. grown beyond its split origin), it's just
+    /// one more candidate, so this never copies nor panics.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.reset_with_report();
+    }
+
+    /// Same as [`reset`](Self::reset), which never panics, so it's safe to be called in `Drop`.
+    ///
+    /// This makes the guarantee explicit for RAII guards returning the buffer on drop.
+    #[inline]
+    pub fn reset_infallible(&mut self) {
+        self.reset();
+    }
+
+    /// Same as [`reset`](Self::reset), but returns an error if the memory couldn't be fully
+    /// reclaimed, see [`ResetReport::reused`].
+    ///
+    /// The buffer is reset in both cases, whatever the nodes are.
+    pub fn try_reset(&mut self) -> Result<(), ResetError> {
+        let report = self.reset_with_report();
+        if report.reused {
+            Ok(())
+        } else {
+            Err(ResetError {
+                reclaimed_bytes: report.reclaimed_bytes,
+            })
+        }
+    }
+
+    /// Same as [`reset`](Self::reset), but reports whether the memory has been fully reclaimed,
+    /// which helps to detect workloads defeating the reuse.
+    pub fn reset_with_report(&mut self) -> ResetReport {
+        // ioslice must be cleared before list
+        self.invalidate_io_slices();
+        self.release_accounting();
+        self.list_len = 0;
+        self.clamp_front(0);
+
+        // coalesce the `BytesMut`s split from the same allocation into runs, whatever the other
+        // nodes are (including foreign `BytesMut`s in between), and keep the largest run as the
+        // new bytes_mut
+        let mut list = std::mem::take(&mut self.list);
+        let bufs = list
+            .drain(..)
+            .filter_map(|node| match node {
+                Node::BytesMut(buf) => Some(buf),
+                _ => None,
+            })
+            // don't forget to unsplit self.bytes
+            .chain(std::iter::once(std::mem::take(&mut self.bytes)));
+        let runs = coalesce_runs(bufs);
+        let breaks = runs.len().saturating_sub(1);
+        let mut largest = None;
+        for run in runs {
+            self.keep_larger(&mut largest, Some(run));
+        }
+        // keep the allocation of the deque, unless it has grown too large after a spike
+        if list.capacity() > self.deque_shrink_threshold {
+            list.shrink_to(DEFAULT_DEQUE_SIZE);
+            #[cfg(not(feature = "safe-only"))]
+            self.ioslice.shrink_to(DEFAULT_DEQUE_SIZE);
+        }
+        self.list = list;
+        self.bytes = largest.unwrap_or_default();
+
+        self.bytes.clear();
+        // each carry-over starts a new run
+        let reused = breaks <= std::mem::take(&mut self.carried_over);
+        ResetReport {
+            reused,
+            reclaimed_bytes: self.bytes.capacity(),
+        }
+    }
+}
+
+// Unstable APIs
+impl LinkedBytes {
+    /// This splits the current bytes_mut and push it to the list.
+    /// This is an unstable API that may change in the future, don't rely on this.
+    /// Returns the index of the node.
+    #[doc(hidden)]
+    #[inline]
+    pub fn split(&mut self) -> usize {
+        let prev = self.bytes.split();
+        #[cfg(not(feature = "safe-only"))]
+        let sealed = !prev.is_empty();
+        let node = Node::BytesMut(prev);
+        self.push_back(node);
+        #[cfg(not(feature = "safe-only"))]
+        if sealed {
+            self.mark_sealed_tail();
+        }
+        self.list.len() - 1
+    }
+
+    /// This gets the node at the given index.
+    /// If you want to get the current bytes_mut, use `bytes_mut()` instead.
+    /// The node may be resized through the returned reference, `len()` walks the list until the
+    /// next change of the list recomputes the cached length.
+    /// This is an unstable API that may change in the future, don't rely on this.
+    #[doc(hidden)]
+    #[inline]
+    pub fn get_list_mut(&mut self, index: usize) -> Option<&mut Node> {
+        self.invalidate_io_slices();
+        self.list_len_dirty = true;
+        self.list.get_mut(index)
+    }
+
+    /// This gets the iterator of the list, including the empty nodes.
+    /// This is an unstable API that may change in the future, don't rely on this.
+    #[doc(hidden)]
+    #[inline]
+    pub fn iter_list(&self) -> impl Iterator<Item = &Node> {
+        self.list.iter()
+    }
+}
+
+/// Returns the non-empty chunks of the list followed by the trailing buffer.
+#[inline]
+fn chunks<'a>(list: &'a VecDeque<Node>, bytes: &'a BytesMut) -> impl Iterator<Item = &'a [u8]> {
+    list.iter()
+        .map(|node| node.as_ref())
+        .chain(std::iter::once(bytes.as_ref()))
+        .filter(|chunk| !chunk.is_empty())
+}
+
+/// Writes all the io slices to `writer`, advancing them in place.
+#[cfg(feature = "safe-only")]
+async fn write_all_io_slices<W: AsyncWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    mut slices: Vec<IoSlice<'_>>,
+    strategy: WritevStrategy,
+    method: &'static str,
+) -> std::io::Result<()> {
+    write_io_slices_safe(writer, &mut slices, strategy, |_| {}, None, method)
+        .await
+        .map_err(WriteError::into_inner)
+}
+
+/// The safe counterpart of the vectored write loop, used with the `safe-only` feature.
+///
+/// The io slices are advanced by [`IoSlice::advance_slices`] instead of raw pointers.
+#[cfg(feature = "safe-only")]
+async fn write_io_slices_safe<W: AsyncWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    slices: &mut [IoSlice<'_>],
+    strategy: WritevStrategy,
+    mut on_progress: impl FnMut(usize),
+    deadline: Option<Instant>,
+    method: &'static str,
+) -> Result<(), WriteError> {
+    let total = slices.len();
+    let mut written = 0;
+    let mut syscalls = 0;
+    let mut slices = slices;
+    while !slices.is_empty() {
+        // only the first `window` io slices are handed to the writer
+        let window = strategy.window(slices);
+        let write = writer.write_vectored(&slices[..window]);
+        let result = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, write).await {
+                Ok(result) => result,
+                Err(_) => Err(std::io::ErrorKind::TimedOut.into()),
+            },
+            None => write.await,
+        };
+        syscalls += 1;
+        let n = match result {
+            Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+            result => result,
+        };
+        let n = match n {
+            Ok(n) => n,
+            Err(e) => {
+                let e = WriteError::new(e, written, total - slices.len());
+                trace_write(method, total, written, syscalls, Some(&e));
+                return Err(e);
+            }
+        };
+        written += n;
+        on_progress(written);
+        IoSlice::advance_slices(&mut slices, n);
+    }
+    trace_write(method, total, written, syscalls, None);
+    Ok(())
+}
+
+/// The sync version of [`write_io_slices_safe`].
+#[cfg(feature = "safe-only")]
+fn sync_write_io_slices_safe<W: std::io::Write + ?Sized>(
+    writer: &mut W,
+    slices: &mut [IoSlice<'_>],
+    strategy: WritevStrategy,
+) -> Result<(), WriteError> {
+    let total = slices.len();
+    let mut written = 0;
+    let mut syscalls = 0;
+    let mut slices = slices;
+    while !slices.is_empty() {
+        // only the first `window` io slices are handed to the writer
+        let window = strategy.window(slices);
+        let result = writer.write_vectored(&slices[..window]);
+        syscalls += 1;
+        let n = match result {
+            Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+            result => result,
+        };
+        let n = match n {
+            Ok(n) => n,
+            // retried as `Write::write_all` does
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                let e = WriteError::new(e, written, total - slices.len());
+                trace_write(
+                    "sync_write_all_vectored",
+                    total,
+                    written,
+                    syscalls,
+                    Some(&e),
+                );
+                return Err(e);
+            }
+        };
+        written += n;
+        IoSlice::advance_slices(&mut slices, n);
+    }
+    trace_write("sync_write_all_vectored", total, written, syscalls, None);
+    Ok(())
+}
+
+/// A pointer to the io slices being written, which can be held across `.await`.
+///
+/// It points into `LinkedBytes::ioslice`, which is mutably borrowed during the whole write, so
+/// it's safe to send it to another thread along with the future.
+#[cfg(not(feature = "safe-only"))]
+#[derive(Clone, Copy)]
+struct SendPtr(*mut IoSlice<'static>);
+
+#[cfg(not(feature = "safe-only"))]
+unsafe impl Send for SendPtr {}
+
+/// Writes all the io slices to `writer`, advancing them in place.
+#[cfg(not(feature = "safe-only"))]
+async fn write_all_io_slices<W: AsyncWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    mut slices: Vec<IoSlice<'_>>,
+    strategy: WritevStrategy,
+    method: &'static str,
+) -> std::io::Result<()> {
+    let total = slices.len();
+    let mut written = 0;
+    let mut syscalls = 0;
+    // the lifetime is erased to reuse `advance_ioslices`, the slices outlive the loop
+    let (mut base_ptr, mut len) = (SendPtr(slices.as_mut_ptr().cast()), slices.len());
+    while len != 0 {
+        let window = strategy.window(unsafe { std::slice::from_raw_parts(base_ptr.0, len) });
+        let ioslice = unsafe { std::slice::from_raw_parts(base_ptr.0, window) };
+        let result = writer.write_vectored(ioslice).await;
+        syscalls += 1;
+        let n = match result {
+            Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+            result => result,
+        };
+        let n = match n {
+            Ok(n) => n,
+            Err(e) => {
+                let e = WriteError::new(e, written, total - len);
+                trace_write(method, total, written, syscalls, Some(&e));
+                return Err(e.into_inner());
+            }
+        };
+        written += n;
+        let (new_ptr, new_window) = unsafe { advance_ioslices(base_ptr.0, window, n) };
+        (base_ptr, len) = (SendPtr(new_ptr), new_window + (len - window));
+    }
+    trace_write(method, total, written, syscalls, None);
+    Ok(())
+}
+
+/// Writes a single chunk to `writer` as `write_all` does, for the paths which don't need the
+/// io slices.
+async fn write_all_chunk<W: AsyncWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    chunk: &[u8],
+    method: &'static str,
+) -> std::io::Result<()> {
+    let iovecs = usize::from(!chunk.is_empty());
+    let mut written = 0;
+    let mut syscalls = 0;
+    while written < chunk.len() {
+        let result = writer.write(&chunk[written..]).await;
+        syscalls += 1;
+        let n = match result {
+            Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+            result => result,
+        };
+        let n = match n {
+            Ok(n) => n,
+            Err(e) => {
+                let e = WriteError::new(e, written, 0);
+                trace_write(method, iovecs, written, syscalls, Some(&e));
+                return Err(e.into_inner());
+            }
+        };
+        written += n;
+    }
+    trace_write(method, iovecs, written, syscalls, None);
+    Ok(())
+}
+
+/// The sync version of [`write_all_chunk`].
+fn sync_write_all_chunk<W: std::io::Write + ?Sized>(
+    writer: &mut W,
+    chunk: &[u8],
+    method: &'static str,
+) -> std::io::Result<()> {
+    let iovecs = usize::from(!chunk.is_empty());
+    let mut written = 0;
+    let mut syscalls = 0;
+    while written < chunk.len() {
+        let result = writer.write(&chunk[written..]);
+        syscalls += 1;
+        let n = match result {
+            Ok(0) => Err(std::io::ErrorKind::WriteZero.into()),
+            result => result,
+        };
+        let n = match n {
+            Ok(n) => n,
+            // retried as `Write::write_all` does
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                let e = WriteError::new(e, written, 0);
+                trace_write(method, iovecs, written, syscalls, Some(&e));
+                return Err(e.into_inner());
+            }
+        };
+        written += n;
+    }
+    trace_write(method, iovecs, written, syscalls, None);
+    Ok(())
+}
+
+/// Advances the io slices by `n` written bytes, returns the new base pointer and length.
+///
+/// `len` should be the number of io slices handed to the writer, so that a writer reporting more
+/// bytes than it was given is caught by the assertions.
+///
+/// # Safety
+///
+/// `base_ptr` must point to `len` valid io slices.
+#[cfg(not(feature = "safe-only"))]
+unsafe fn advance_ioslices(
+    base_ptr: *mut IoSlice<'static>,
+    len: usize,
+    n: usize,
+) -> (*mut IoSlice<'static>, usize) {
+    let ioslice = std::slice::from_raw_parts(base_ptr, len);
+    // Number of buffers to remove.
+    let mut remove = 0;
+    // Total length of all the to be removed buffers.
+    let mut accumulated_len = 0;
+    for buf in ioslice.iter() {
+        if accumulated_len + buf.len() > n {
+            break;
+        } else {
+            accumulated_len += buf.len();
+            remove += 1;
+        }
+    }
+
+    // adjust the outer [IoSlice]
+    let base_ptr = base_ptr.add(remove);
+    let len = len - remove;
+    if len == 0 {
+        assert!(
+            n == accumulated_len,
+            "advancing io slices beyond their length"
+        );
+    } else {
+        // adjust the inner IoSlice
+        let inner_slice = &mut *base_ptr;
+        let (inner_ptr, inner_len) = (inner_slice.as_ptr(), inner_slice.len());
+        let remaining = n - accumulated_len;
+        assert!(
+            remaining <= inner_len,
+            "advancing io slice beyond its length"
+        );
+        let new_ptr = inner_ptr.add(remaining);
+        let new_len = inner_len - remaining;
+        *inner_slice = IoSlice::new(std::slice::from_raw_parts(new_ptr, new_len));
+    }
+    (base_ptr, len)
+}
+
+/// Coalesces the `BytesMut`s split from the same allocation into runs, whatever the order they
+/// come in (including foreign `BytesMut`s in between).
+fn coalesce_runs(bufs: impl IntoIterator<Item = BytesMut>) -> Vec<BytesMut> {
+    let mut runs: Vec<BytesMut> = Vec::new();
+    for buf in bufs {
+        // an empty buffer has no memory to reclaim
+        if buf.capacity() == 0 {
+            continue;
+        }
+        // the latest run is the usual one to continue
+        let unmerged =
+            runs.iter_mut()
+                .rev()
+                .try_fold(buf, |buf, run| match try_unsplit(run, buf) {
+                    Ok(()) => ControlFlow::Break(()),
+                    Err(buf) => ControlFlow::Continue(buf),
+                });
+        if let ControlFlow::Continue(buf) = unmerged {
+            // the buffer has been reallocated or replaced, or isn't split from the same
+            // allocation as any run
+            runs.push(buf);
+        }
+    }
+    runs
+}
+
+/// Merges `other` into `head` without copying if `other` directly follows `head` in memory,
+/// otherwise returns `other` back.
+///
+/// This is used by `reset`, so the content of `other` is not preserved if it's empty.
+#[inline]
+fn try_unsplit(head: &mut BytesMut, other: BytesMut) -> Result<(), BytesMut> {
+    if other.capacity() == 0 {
+        return Ok(());
+    }
+    if head.as_ptr().wrapping_add(head.len()) == other.as_ptr() {
+        head.unsplit(other);
+        Ok(())
+    } else {
+        Err(other)
+    }
+}
+
+/// Creates the span of a public write method, whose fields are recorded by `trace_write`.
+#[cfg(feature = "tracing")]
+fn write_span(method: &'static str) -> tracing::Span {
+    tracing::trace_span!(
+        "linkedbytes_write",
+        method,
+        iovecs = tracing::field::Empty,
+        bytes = tracing::field::Empty,
+        syscalls = tracing::field::Empty,
+    )
+}
+
+/// Runs the future of a public write method in its span, compiled out unless the `tracing`
+/// feature is enabled.
+#[inline]
+#[allow(unused_variables)]
+fn in_write_span<F: std::future::Future>(
+    method: &'static str,
+    future: F,
+) -> impl std::future::Future<Output = F::Output> {
+    #[cfg(feature = "tracing")]
+    return tracing::Instrument::instrument(future, write_span(method));
+    #[cfg(not(feature = "tracing"))]
+    future
+}
+
+/// Emits a `tracing` event describing a finished vectored write and records its counts in the
+/// current write span, compiled out unless the `tracing` feature is enabled.
+#[inline]
+#[allow(unused_variables)]
+fn trace_write(
+    method: &'static str,
+    iovecs: usize,
+    bytes: usize,
+    syscalls: usize,
+    error: Option<&WriteError>,
+) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current()
+        .record("iovecs", iovecs)
+        .record("bytes", bytes)
+        .record("syscalls", syscalls);
+    #[cfg(feature = "tracing")]
+    match error {
+        None => tracing::trace!(method, iovecs, bytes, syscalls, "vectored write finished"),
+        Some(e) => {
+            tracing::debug!(method, iovecs, bytes, syscalls, error = %e, "vectored write failed")
+        }
+    }
+}
+
+/// Adds `len` to `total`, panicking on overflow in debug builds and saturating in release builds.
+#[inline]
+fn add_len(total: usize, len: usize) -> usize {
+    debug_assert!(
+        total.checked_add(len).is_some(),
+        "length of LinkedBytes overflows usize"
+    );
+    total.saturating_add(len)
+}
+
+/// Sums up the lengths, returning `None` on overflow.
+#[inline]
+fn checked_sum_len(lens: impl IntoIterator<Item = usize>) -> Option<usize> {
+    lens.into_iter()
+        .try_fold(0usize, |total, len| total.checked_add(len))
+}
+
+impl Drop for LinkedBytes {
+    #[inline]
+    fn drop(&mut self) {
+        self.release_accounting();
+    }
+}
+
+impl Default for LinkedBytes {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Bytes> for LinkedBytes {
+    #[inline]
+    fn from(bytes: Bytes) -> Self {
+        let mut this = Self::with_capacity(0);
+        this.insert(bytes);
+        this
+    }
+}
+
+impl From<&'static [u8]> for LinkedBytes {
+    #[inline]
+    fn from(data: &'static [u8]) -> Self {
+        let mut this = Self::with_capacity(0);
+        this.insert_static(data);
+        this
+    }
+}
+
+impl From<BytesMut> for LinkedBytes {
+    #[inline]
+    fn from(bytes: BytesMut) -> Self {
+        let mut this = Self::with_capacity(0);
+        this.bytes = bytes;
+        this
+    }
+}
+
+impl From<Vec<u8>> for LinkedBytes {
+    #[inline]
+    fn from(vec: Vec<u8>) -> Self {
+        // this doesn't copy, since the `Bytes` is unique
+        BytesMut::from(Bytes::from(vec)).into()
+    }
+}
+
+impl From<String> for LinkedBytes {
+    #[inline]
+    fn from(s: String) -> Self {
+        s.into_bytes().into()
+    }
+}
+
+impl PartialEq<str> for LinkedBytes {
+    fn eq(&self, other: &str) -> bool {
+        let mut rest = other.as_bytes();
+        if rest.len() != self.len() {
+            return false;
+        }
+        self.iter_chunks().all(|chunk| {
+            let (head, tail) = rest.split_at(chunk.len());
+            rest = tail;
+            head == chunk
+        })
+    }
+}
+
+impl PartialEq<&str> for LinkedBytes {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<String> for LinkedBytes {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        *self == **other
+    }
+}
+
+/// Reads the content from the front, consuming the nodes as they are read past.
+///
+/// This allows to decode from a `LinkedBytes` without copying it into a contiguous buffer first,
+/// advancing past the end of the list continues into the current bytes_mut.
+impl Buf for LinkedBytes {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.iter_chunks().next().unwrap_or_default()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.len(),
+            "cannot advance past `remaining`: {:?} <= {:?}",
+            cnt,
+            self.len()
+        );
+        if cnt > 0 {
+            self.drop_front_bytes(cnt);
+        }
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        let slices = self
+            .iter_chunks()
+            .flat_map(|chunk| chunk.chunks(MAX_IOSLICE_LEN));
+        let mut n = 0;
+        for (slot, slice) in dst.iter_mut().zip(slices) {
+            *slot = IoSlice::new(slice);
+            n += 1;
+        }
+        n
+    }
+
+    #[inline]
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        self.take_bytes(len)
+    }
+}
+
+// SAFETY: `BufMut` is an unsafe trait, so it must be implemented unsafely even with the
+// `safe-only` feature. Its contract (`chunk_mut` and `advance_mut` agreeing on the initialized
+// bytes) is upheld by forwarding both to the current bytes_mut, `chunk_mut` only shortens the
+// chunk to the size limit.
+#[allow(unsafe_code)]
+unsafe impl BufMut for LinkedBytes {
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+        match self.size_limit {
+            Some(limit) => limit.saturating_sub(self.len()),
+            None => self.bytes.remaining_mut(),
+        }
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.bytes.advance_mut(cnt)
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut bytes::buf::UninitSlice {
+        // `BytesMut` reserves more space when it's full (e.g. right after an `insert` split), so
+        // the returned slice is never empty, unless the size limit is reached
+        match self.size_limit {
+            Some(_) => {
+                let allowance = self.remaining_mut();
+                let chunk = self.bytes.chunk_mut();
+                let len = allowance.min(chunk.len());
+                &mut chunk[..len]
+            }
+            None => self.bytes.chunk_mut(),
+        }
+    }
+
+    #[inline]
+    fn put_slice(&mut self, src: &[u8]) {
+        if self.size_limit.is_some() {
+            assert!(
+                self.remaining_mut() >= src.len(),
+                "size limit exceeded: remaining {:?}, put {:?}",
+                self.remaining_mut(),
+                src.len()
+            );
+        }
+        let spare = self.bytes.capacity() - self.bytes.len();
+        if src.len() > spare && self.bytes.len() >= SEAL_ON_GROW_LEN && !self.contiguous {
+            // growing would copy the large content, start a new buffer for `src` instead
+            self.seal();
+            self.bytes
+                .reserve(LinkedBytes::recommended_capacity(src.len()));
+        }
+        self.bytes.extend_from_slice(src);
+    }
+}
+
+/// Writes go to the current bytes_mut, which grows as needed, so they never fail.
+///
+/// `&mut LinkedBytes` also implements [`std::io::Write`] through the blanket impl in `std`, so it
+/// can be passed to helpers taking `W: Write` directly.
+impl std::io::Write for LinkedBytes {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.bytes.extend_from_slice(buf);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+        let len = self.len();
+        self.extend_from_io_slices(bufs);
+        Ok(self.len() - len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Formats into the current bytes_mut, e.g. `write!(buf, "Content-Length: {}\r\n", len)`
+/// for text protocols, without a temporary `String`.
+impl std::fmt::Write for LinkedBytes {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.bytes.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl std::ops::Deref for ReservedNode<'_> {
+    type Target = BytesMut;
+
+    #[inline]
+    fn deref(&self) -> &BytesMut {
+        &self.buf
+    }
+}
+
+impl std::ops::DerefMut for ReservedNode<'_> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buf
+    }
+}
+
+impl Drop for ReservedNode<'_> {
+    fn drop(&mut self) {
+        let buf = std::mem::take(&mut self.buf);
+        if buf.is_empty() {
+            self.linked.recycle_node(Node::BytesMut(buf));
+            return;
+        }
+        self.linked.account(buf.len());
+        if self.linked.contiguous {
+            self.linked.bytes.extend_from_slice(&buf);
+            self.linked.recycle_node(Node::BytesMut(buf));
+            return;
+        }
+        self.linked.split_before_insert();
+        self.linked.push_back(Node::BytesMut(buf));
+    }
+}
+
+#[cfg(test)]
+// the tests exercise the unsafe `BufMut` API as well
+#[allow(unsafe_code)]
+mod tests {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use super::*;
+
+    /// A step of the script of [`MockVectoredWriter`].
+    #[derive(Debug, Clone, Copy)]
+    enum Step {
+        /// Accepts at most this many bytes, `0` makes the write return `Ok(0)`.
+        Accept(usize),
+        /// Writes nothing, returns `Pending` (async) or `Interrupted` (sync).
+        Skip,
+        /// Fails with an error of this kind.
+        Fail(std::io::ErrorKind),
+        /// Accepts all the bytes given.
+        All,
+    }
+
+    /// A writer whose `write_vectored` calls follow a script, and accept all the bytes once the
+    /// script is over.
+    #[derive(Debug, Default)]
+    struct MockVectoredWriter {
+        script: VecDeque<Step>,
+        data: Vec<u8>,
+        calls: usize,
+    }
+
+    impl MockVectoredWriter {
+        fn new(script: impl IntoIterator<Item = Step>) -> Self {
+            Self {
+                script: script.into_iter().collect(),
+                ..Default::default()
+            }
+        }
+
+        /// Runs the next step, returns `None` for [`Step::Skip`].
+        fn step(&mut self, bufs: &[IoSlice<'_>]) -> Option<std::io::Result<usize>> {
+            self.calls += 1;
+            let max = match self.script.pop_front().unwrap_or(Step::All) {
+                Step::Accept(n) => n,
+                Step::All => usize::MAX,
+                Step::Skip => return None,
+                Step::Fail(kind) => return Some(Err(kind.into())),
+            };
+            let mut n = 0;
+            for buf in bufs {
+                let take = buf.len().min(max - n);
+                self.data.extend_from_slice(&buf[..take]);
+                n += take;
+                if n == max {
+                    break;
+                }
+            }
+            Some(Ok(n))
+        }
+    }
+
+    impl std::io::Write for MockVectoredWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            std::io::Write::write_vectored(self, &[IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+            self.step(bufs)
+                .unwrap_or_else(|| Err(std::io::ErrorKind::Interrupted.into()))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl AsyncWrite for MockVectoredWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.poll_write_vectored(cx, &[IoSlice::new(buf)])
+        }
+
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<std::io::Result<usize>> {
+            match self.get_mut().step(bufs) {
+                Some(result) => Poll::Ready(result),
+                None => {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            true
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// A writer recording the lengths of the slices of every write call, besides the written bytes,
+    /// to check how a [`LinkedBytes`] is chunked when written out without a real socket.
+    ///
+    /// Every call accepts all the given bytes.
+    #[derive(Debug, Default)]
+    struct RecordingWriter {
+        /// The concatenation of everything written.
+        data: Vec<u8>,
+        /// The slice lengths of each vectored write call, in order.
+        vectored_calls: Vec<Vec<usize>>,
+        /// The length of each plain write call, in order.
+        plain_calls: Vec<usize>,
+    }
+
+    impl RecordingWriter {
+        fn record_plain(&mut self, buf: &[u8]) -> usize {
+            self.plain_calls.push(buf.len());
+            self.data.extend_from_slice(buf);
+            buf.len()
+        }
+
+        fn record_vectored(&mut self, bufs: &[IoSlice<'_>]) -> usize {
+            self.vectored_calls
+                .push(bufs.iter().map(|buf| buf.len()).collect());
+            let start = self.data.len();
+            for buf in bufs {
+                self.data.extend_from_slice(buf);
+            }
+            self.data.len() - start
+        }
+    }
+
+    impl std::io::Write for RecordingWriter {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(self.record_plain(buf))
+        }
+
+        #[inline]
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+            Ok(self.record_vectored(bufs))
+        }
+
+        #[inline]
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl AsyncWrite for RecordingWriter {
+        #[inline]
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            Poll::Ready(Ok(self.get_mut().record_plain(buf)))
+        }
+
+        #[inline]
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<std::io::Result<usize>> {
+            Poll::Ready(Ok(self.get_mut().record_vectored(bufs)))
+        }
+
+        #[inline]
+        fn is_write_vectored(&self) -> bool {
+            true
+        }
+
+        #[inline]
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        #[inline]
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Builds a buffer of several kinds of chunks: "head-" | "bytes-" | "static-" | "mid-" |
+    /// "faststr-" | "tail".
+    fn mixed() -> LinkedBytes {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"head-");
+        buf.insert(Bytes::from_static(b"bytes-"));
+        buf.insert_static(b"static-");
+        buf.put_slice(b"mid-");
+        buf.insert_faststr(FastStr::from_static_str("faststr-"));
+        buf.put_slice(b"tail");
+        buf
+    }
+
+    fn scripts() -> Vec<Vec<Step>> {
+        let len = mixed().len();
+        vec![
+            vec![Step::All],
+            vec![
+                Step::Accept(3),
+                Step::Skip,
+                Step::Accept(100),
+                Step::Accept(1),
+                Step::All,
+            ],
+            vec![Step::Accept(1); len],
+            // exactly on the chunk boundaries
+            vec![
+                Step::Accept(5),
+                Step::Accept(6),
+                Step::Accept(7),
+                Step::Accept(4),
+                Step::Accept(8),
+            ],
+            // across the chunk boundaries
+            vec![Step::Accept(4), Step::Accept(9), Step::Accept(13)],
+            vec![
+                Step::Skip,
+                Step::Skip,
+                Step::Accept(2),
+                Step::Skip,
+                Step::All,
+            ],
+            vec![Step::Accept(len - 1), Step::Accept(1)],
+        ]
+    }
+
+    #[test]
+    fn sync_write_all_vectored_partial_writes() {
+        for script in scripts() {
+            let mut buf = mixed();
+            let mut writer = MockVectoredWriter::new(script.clone());
+            buf.sync_write_all_vectored(&mut writer).unwrap();
+            assert_eq!(writer.data, buf.concat(), "script {script:?}");
+            buf.verify_invariants();
+        }
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored_partial_writes() {
+        for script in scripts() {
+            let mut buf = mixed();
+            let mut writer = MockVectoredWriter::new(script.clone());
+            buf.write_all_vectored(&mut writer).await.unwrap();
+            assert_eq!(writer.data, buf.concat(), "script {script:?}");
+            buf.verify_invariants();
+        }
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored_partial_writes_in_windows() {
+        let strategies = [
+            WritevStrategy {
+                iov_max: 1,
+                ..WritevStrategy::DEFAULT
+            },
+            WritevStrategy {
+                iov_max: 2,
+                max_batch_bytes: 10,
+                ..WritevStrategy::DEFAULT
+            },
+        ];
+        for strategy in strategies {
+            for script in scripts() {
+                let mut buf = mixed();
+                buf.set_writev_strategy(strategy);
+                let mut writer = MockVectoredWriter::new(script.clone());
+                buf.write_all_vectored(&mut writer).await.unwrap();
+                assert_eq!(writer.data, buf.concat(), "{strategy:?} {script:?}");
+
+                let mut writer = MockVectoredWriter::new(script.clone());
+                buf.sync_write_all_vectored(&mut writer).unwrap();
+                assert_eq!(writer.data, buf.concat(), "{strategy:?} {script:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn sync_write_all_vectored_write_zero() {
+        let mut buf = mixed();
+        let mut writer = MockVectoredWriter::new([Step::Accept(7), Step::Accept(0)]);
+        let e = buf
+            .sync_write_all_vectored_detailed(&mut writer)
+            .unwrap_err();
+        assert_eq!(e.error().kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(e.bytes_written(), 7);
+        assert_eq!(e.last_chunk_index(), 1);
+        assert_eq!(writer.data, b"head-by");
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored_error() {
+        let mut buf = mixed();
+        let mut writer = MockVectoredWriter::new([
+            Step::Accept(11),
+            Step::Skip,
+            Step::Fail(std::io::ErrorKind::BrokenPipe),
+        ]);
+        let e = buf
+            .write_all_vectored_detailed(&mut writer)
+            .await
+            .unwrap_err();
+        assert_eq!(e.error().kind(), std::io::ErrorKind::BrokenPipe);
+        assert_eq!(e.bytes_written(), 11);
+        assert_eq!(e.last_chunk_index(), 2);
+        assert_eq!(writer.calls, 3);
+    }
+
+    #[test]
+    fn write_error_counts_chunks_after_begin_frame() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"aa");
+        buf.begin_frame();
+        buf.put_slice(b"bb");
+        // the node split by `begin_frame` and the bytes_mut are separate io slices
+        assert_eq!(buf.io_slice().len(), 2);
+        let mut writer =
+            MockVectoredWriter::new([Step::Accept(3), Step::Fail(std::io::ErrorKind::BrokenPipe)]);
+        let e = buf
+            .sync_write_all_vectored_detailed(&mut writer)
+            .unwrap_err();
+        assert_eq!(e.bytes_written(), 3);
+        assert_eq!(e.last_chunk_index(), 1);
+        assert_eq!(buf.chunks_covered_by(3), (1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "front slot handle is invalidated")]
+    fn front_slot_split_off() {
+        let mut buf = LinkedBytes::new();
+        let handle = buf.reserve_front_slot(4);
+        buf.put_slice(b"body");
+        let tail = buf.split_off_bytes(0);
+        assert_eq!(&tail[..], b"\0\0\0\0body");
+        buf.put_slice(b"abcd");
+        buf.insert(Bytes::from_static(b"efgh"));
+        buf.verify_invariants();
+        // the slot has been split off, "abcd" must not be overwritten
+        buf.fill_front_slot(handle, b"HDR!");
+    }
+
+    #[test]
+    fn front_slot_split_off_after_slot() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"body");
+        let handle = buf.reserve_front_slot(2);
+        let tail = buf.split_off_bytes(4);
+        assert_eq!(&tail[..], b"dy");
+        buf.fill_front_slot(handle, b"HI");
+        assert_eq!(&buf.concat()[..], b"HIbo");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "front slot handle is invalidated")]
+    fn front_slot_consumed() {
+        let mut buf = LinkedBytes::new();
+        let handle = buf.reserve_front_slot(4);
+        buf.consume_front_nodes(1);
+        // the new slot has the same position as the consumed one
+        let _ = buf.reserve_front_slot(4);
+        buf.fill_front_slot(handle, b"HDR!");
+    }
+
+    #[test]
+    fn accounting_tracks_inserts_and_resets() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut buf = LinkedBytes::with_accounting(counter.clone());
+        buf.insert(Bytes::from(vec![0; 100]));
+        buf.insert_static(&[1; 50]);
+        assert_eq!(counter.load(Ordering::Relaxed), 150);
+        buf.reset();
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        buf.insert(Bytes::from(vec![0; 10]));
+        drop(buf);
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn accounting_covers_slots_placeholders_and_replacements() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let load = || counter.load(Ordering::Relaxed);
+        let mut buf = LinkedBytes::with_accounting(counter.clone());
+        let slot = buf.reserve_front_slot(4);
+        assert_eq!(load(), 4);
+        let placeholder = buf.insert_placeholder();
+        let varint = buf.reserve_varint_slot();
+        buf.insert(Bytes::from(vec![0; 300]));
+        assert_eq!(load(), 304);
+        buf.fill_front_slot(slot, b"HDR!");
+        buf.fill_varint_slot(varint, 300);
+        buf.fill_placeholder(placeholder, Bytes::from_static(b"ph"));
+        assert_eq!(load(), 308);
+        assert_eq!(load(), buf.len());
+
+        // the replaced node is released, the new one is accounted
+        let index = buf.list.len() - 1;
+        buf.replace_node(index, Node::Static(b"short"));
+        assert_eq!(load(), 13);
+        assert_eq!(load(), buf.len());
+
+        let dst_counter = Arc::new(AtomicUsize::new(0));
+        let mut dst = LinkedBytes::with_accounting(dst_counter.clone());
+        buf.clone_into(&mut dst);
+        assert_eq!(dst_counter.load(Ordering::Relaxed), dst.len());
+
+        buf.reset();
+        drop(dst);
+        assert_eq!(load(), 0);
+        assert_eq!(dst_counter.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn accounting_released_on_removal() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let load = || counter.load(Ordering::Relaxed);
+        let mut buf = LinkedBytes::with_accounting(counter.clone());
+        buf.insert(Bytes::from(vec![0; 100]));
+        buf.insert_static(&[1; 50]);
+        buf.advance(150);
+        assert_eq!(buf.len(), 0);
+        assert_eq!(load(), 0);
+
+        buf.put_slice(b"written");
+        buf.insert(Bytes::from(vec![0; 100]));
+        buf.advance(57);
+        assert_eq!(load(), 50);
+        let _ = buf.take_bytes(50);
+        assert_eq!(load(), 0);
+
+        buf.insert(Bytes::from(vec![0; 100]));
+        buf.insert_static(&[1; 50]);
+        let _ = buf.split_off_bytes(120);
+        assert_eq!(load(), 120);
+        buf.truncate_nodes(0);
+        assert_eq!(load(), 0);
+
+        buf.insert(Bytes::from(vec![0; 100]));
+        buf.insert_static(&[1; 50]);
+        let tail = buf.split_off_at_chunk(1);
+        assert_eq!(tail.len(), 50);
+        assert_eq!(load(), 100);
+        buf.consume_front_nodes(buf.node_count());
+        assert_eq!(load(), 0);
+
+        // the copies of contiguous mode are released along with the bytes_mut
+        buf.set_contiguous_mode(true);
+        buf.insert(Bytes::from(vec![0; 100]));
+        assert_eq!(load(), 100);
+        buf.advance(100);
+        assert_eq!(load(), 0);
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn repeated_frames_reuse_allocations() {
+        let mut buf = LinkedBytes::new();
+        let capacity = buf.capacity();
+        for i in 0..3u8 {
+            buf.begin_frame();
+            buf.put_slice(&[b'a' + i; 4]);
+            buf.insert_static(b"-body-");
+            buf.put_slice(&[b'x' + i; 2]);
+            let frame = buf.end_frame();
+            let chunks: Vec<&[u8]> = buf.frame_chunks(frame).collect();
+            assert_eq!(
+                chunks.concat(),
+                [&[b'a' + i; 4][..], b"-body-", &[b'x' + i; 2]].concat()
+            );
+            assert_eq!(buf.frame_io_slice(frame).len(), 3);
+            let report = buf.reset_with_report();
+            assert!(report.reused);
+            assert_eq!(buf.capacity(), capacity);
+        }
+    }
+
+    #[test]
+    fn rotate_reuses_frame_allocations_and_keeps_the_rest() {
+        let mut buf = LinkedBytes::new();
+        buf.insert_static(b"preamble");
+        let capacity = buf.capacity();
+        let base = buf.bytes.as_ptr();
+        for i in 0..3u8 {
+            buf.begin_frame();
+            buf.put_slice(&[b'a' + i; 4]);
+            buf.insert_static(b"-body-");
+            buf.put_slice(&[b'x' + i; 2]);
+            let frame = buf.end_frame();
+            assert_eq!(
+                buf.frame_chunks(frame).collect::<Vec<_>>().concat(),
+                [&[b'a' + i; 4][..], b"-body-", &[b'x' + i; 2]].concat()
+            );
+            buf.rotate(frame);
+            // only the frame's nodes are removed, the same allocation is written next
+            assert!(buf == "preamble");
+            assert_eq!(buf.list.len(), 1);
+            assert_eq!(buf.capacity(), capacity);
+            assert_eq!(buf.bytes.as_ptr(), base);
+            buf.verify_invariants();
+        }
+    }
+
+    #[test]
+    fn rotate_keeps_pending_bytes_and_later_frames() {
+        let mut buf = LinkedBytes::new();
+        buf.begin_frame();
+        buf.put_slice(b"first");
+        let first = buf.end_frame();
+        buf.begin_frame();
+        buf.put_slice(b"second");
+        buf.insert_static(b"!");
+        buf.end_frame();
+        buf.put_slice(b"pending");
+        buf.rotate(first);
+        assert!(buf == "second!pending");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "the frame is invalidated")]
+    fn rotate_panics_on_invalidated_frame() {
+        let mut buf = LinkedBytes::new();
+        buf.begin_frame();
+        buf.put_slice(b"frame");
+        let frame = buf.end_frame();
+        buf.clear();
+        buf.rotate(frame);
+    }
+
+    #[test]
+    fn reset_with_non_bytes_mut_head() {
+        let builders: Vec<fn() -> LinkedBytes> = vec![
+            || LinkedBytes::from_static_parts([&b"a"[..], b"", b"bc"]),
+            || Bytes::from_static(b"bytes").into(),
+            || {
+                let mut buf = LinkedBytes::new();
+                buf.insert(Bytes::from_static(b"first"));
+                buf.put_slice(b"body");
+                buf
+            },
+            || {
+                let mut buf = LinkedBytes::new();
+                buf.put_slice(b"body");
+                buf.prepend_static(b"magic");
+                buf.insert_front(Bytes::from_static(b"len"));
+                buf.insert_faststr_front(FastStr::from_static_str("str"));
+                buf.prepend_many([Bytes::from_static(b"x"), Bytes::from_static(b"y")]);
+                buf
+            },
+            || {
+                let mut buf = LinkedBytes::new();
+                buf.put_slice(b"body");
+                buf.insert(Bytes::from_static(b"0123456789"));
+                buf.rebalance(3);
+                buf
+            },
+            || {
+                let mut buf = LinkedBytes::new();
+                buf.put_slice(b"body");
+                let _ = buf.snapshot();
+                buf.put_slice(b"more");
+                buf
+            },
+            || {
+                let list = VecDeque::from([Node::Bytes(Bytes::from_static(b"head"))]);
+                LinkedBytes::from_parts(list, BytesMut::from(&b"tail"[..]))
+            },
+        ];
+        for (i, build) in builders.into_iter().enumerate() {
+            let mut buf = build();
+            buf.verify_invariants();
+            buf.reset();
+            assert!(buf.is_empty(), "builder {i}");
+            assert_eq!(buf.node_count(), 0, "builder {i}");
+            buf.put_slice(b"next");
+            buf.insert(Bytes::from_static(b"!"));
+            assert_eq!(&buf.concat()[..], b"next!", "builder {i}");
+            buf.verify_invariants();
+        }
+    }
+
+    #[test]
+    fn no_empty_head_node() {
+        let buf = LinkedBytes::from_static_parts([&b"a"[..], b"", b"bc"]);
+        assert_eq!(buf.node_count(), 2);
+        assert_eq!(buf.io_slice().len(), 2);
+
+        let mut buf = LinkedBytes::new();
+        buf.insert(Bytes::from_static(b"frozen"));
+        let snapshot = buf.snapshot();
+        assert_eq!(&snapshot[..], b"frozen");
+        assert_eq!(buf.node_count(), 1);
+
+        buf.put_slice(b"0123");
+        buf.rebalance(2);
+        assert_eq!(buf.node_count(), 5);
+        assert!(buf.iter_list().all(|node| !node.as_ref().is_empty()));
+    }
+
+    #[test]
+    fn from_static_parts_keeps_order() {
+        let parts: [&'static [u8]; 3] = [b"GET ", b"/index", b" HTTP/1.1\r\n"];
+        let mut buf = LinkedBytes::from_static_parts(parts);
+        assert!(buf.iter_list().all(|node| matches!(node, Node::Static(_))));
+        assert!(buf.bytes().is_empty());
+        let slices = buf.io_slice();
+        assert_eq!(slices.len(), 3);
+        for (slice, part) in slices.iter().zip(parts) {
+            assert_eq!(&slice[..], part);
+        }
+        buf.put_slice(b"Host: a\r\n");
+        assert_eq!(&buf.concat()[..], b"GET /index HTTP/1.1\r\nHost: a\r\n");
+    }
+
+    #[test]
+    fn len_accumulation_overflow() {
+        // lengths of a 32-bit target buffering near 4GB
+        let gb = 1usize << 30;
+        assert_eq!(checked_sum_len([gb, gb, gb]), Some(3 * gb));
+        assert_eq!(checked_sum_len([usize::MAX - 1, 1]), Some(usize::MAX));
+        assert_eq!(checked_sum_len([usize::MAX - 1, 1, 1]), None);
+        assert_eq!(add_len(usize::MAX - 2, 2), usize::MAX);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "length of LinkedBytes overflows usize")]
+    fn len_accumulation_overflow_panics_in_debug() {
+        add_len(usize::MAX, 1);
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn len_accumulation_overflow_saturates_in_release() {
+        assert_eq!(add_len(usize::MAX, 1), usize::MAX);
+    }
+
+    #[test]
+    fn get_list_mut_resize_keeps_len() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"head");
+        let index = buf.split();
+        buf.insert(Bytes::from_static(b"-body"));
+        match buf.get_list_mut(index) {
+            Some(Node::BytesMut(node)) => node.extend_from_slice(b"er"),
+            _ => unreachable!(),
+        }
+        assert_eq!(buf.len(), 11);
+        assert_eq!(buf.try_len(), Some(11));
+        buf.verify_invariants();
+        buf.insert(Bytes::from_static(b"!"));
+        assert_eq!(buf.len(), 12);
+        assert_eq!(&buf.concat()[..], b"header-body!");
+        buf.verify_invariants();
+
+        match buf.get_list_mut(index) {
+            Some(Node::BytesMut(node)) => node.truncate(1),
+            _ => unreachable!(),
+        }
+        assert_eq!(buf.len(), 7);
+        buf.advance(2);
+        assert_eq!(buf.len(), 5);
+        assert_eq!(&buf.concat()[..], b"body!");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    #[cfg(not(feature = "safe-only"))]
+    fn io_slice_cached_manual_write_vectored() {
+        let mut buf = mixed();
+        let expected = buf.concat();
+        let mut out = Vec::new();
+        let n = std::io::Write::write_vectored(&mut out, buf.io_slice_cached()).unwrap();
+        assert_eq!(n, expected.len());
+        assert_eq!(out, expected);
+        let ptr = buf.io_slice_cached().as_ptr() as usize;
+        assert_eq!(buf.io_slice_cached().as_ptr() as usize, ptr);
+
+        buf.insert(Bytes::from_static(b"!"));
+        assert_eq!(buf.io_slice_cached().len(), 7);
+    }
+
+    #[test]
+    fn insert_writes_multiple_io_slices() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"header");
+        buf.insert(Bytes::from(vec![1; 4096]));
+        buf.put_slice(b"trailer");
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        // the inserted `Bytes` is written as is, not copied into a single buffer
+        assert_eq!(writer.vectored_calls, [vec![6, 4096, 7]]);
+        assert!(writer.plain_calls.is_empty());
+        assert_eq!(writer.data, buf.concat());
+    }
+
+    #[tokio::test]
+    async fn insert_writes_multiple_io_slices_async() {
+        let mut buf = mixed();
+        let mut writer = RecordingWriter::default();
+        buf.write_all_vectored(&mut writer).await.unwrap();
+        assert_eq!(writer.vectored_calls, [vec![5, 6, 7, 4, 8, 4]]);
+        assert_eq!(writer.data, buf.concat());
+
+        // only the bytes_mut, which is written by a plain write
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"plain");
+        let mut writer = RecordingWriter::default();
+        buf.write_all_vectored(&mut writer).await.unwrap();
+        assert!(writer.vectored_calls.is_empty());
+        assert_eq!(writer.plain_calls, [5]);
+    }
+
+    #[test]
+    fn try_reset_pathological_heads() {
+        let mut buf = LinkedBytes::from_parts(
+            VecDeque::from([Node::Bytes(Bytes::from_static(b"bytes"))]),
+            BytesMut::new(),
+        );
+        assert!(buf.try_reset().is_ok());
+        assert!(buf.is_empty());
+
+        let mut buf = LinkedBytes::from_parts(
+            VecDeque::from([
+                Node::FastStr(FastStr::from_static_str("faststr")),
+                Node::BytesMut(BytesMut::from(&b"mut"[..])),
+            ]),
+            BytesMut::with_capacity(16),
+        );
+        let _ = buf.try_reset();
+        assert!(buf.is_empty());
+        buf.put_slice(b"ok");
+        assert_eq!(&buf.concat()[..], b"ok");
+
+        // empty list with dirty bytes
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"dirty");
+        assert_eq!(buf.try_reset(), Ok(()));
+        assert!(buf.is_empty());
+
+        let mut buf = LinkedBytes::with_capacity(0);
+        buf.insert(Bytes::from_static(b"only"));
+        buf.reset();
+        buf.reset_infallible();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn try_reset_after_carry_over() {
+        let mut buf = LinkedBytes::with_capacity(64);
+        let capacity = buf.capacity();
+        // fill the bytes_mut, insert, then write into the carried over buffer
+        buf.put_slice(&vec![1; capacity]);
+        buf.insert(Bytes::from_static(b"inserted"));
+        buf.put_slice(b"after");
+        assert_eq!(buf.try_reset(), Ok(()));
+        assert!(buf.capacity() >= capacity);
+
+        // growing the bytes_mut loses the reuse
+        let mut buf = LinkedBytes::with_capacity(64);
+        buf.put_slice(b"head");
+        buf.insert(Bytes::from_static(b"inserted"));
+        buf.put_slice(&[1; 1024]);
+        let e = buf.try_reset().unwrap_err();
+        assert_eq!(e.reclaimed_bytes(), buf.capacity());
+    }
+
+    #[test]
+    fn reserve_writable_fills_in_place() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"head-");
+        buf.reserve_writable(4).copy_from_slice(b"slot");
+        buf.reserve_writable(2).copy_from_slice(b"!!");
+        buf.put_slice(b"-tail");
+        // no empty node between the back to back slots
+        assert_eq!(buf.node_count(), 3);
+        let slices: Vec<Vec<u8>> = buf.io_slice().iter().map(|s| s.to_vec()).collect();
+        assert_eq!(slices, [&b"head-"[..], b"slot", b"!!", b"-tail"]);
+        buf.verify_invariants();
+
+        let mut buf = LinkedBytes::new();
+        buf.set_contiguous_mode(true);
+        buf.put_slice(b"head-");
+        buf.reserve_writable(4).copy_from_slice(b"slot");
+        {
+            let mut node = buf.reserve_as_node(16);
+            node.extend_from_slice(b"-node");
+        }
+        assert_eq!(buf.node_count(), 0);
+        assert_eq!(&buf.concat()[..], b"head-slot-node");
+    }
+
+    #[test]
+    fn slots_dont_push_empty_nodes() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut buf = LinkedBytes::with_accounting(counter.clone());
+        let placeholder = buf.insert_placeholder();
+        let varint = buf.reserve_varint_slot();
+        buf.put_slice(b"body");
+        // only the two slots, no empty split in front of them
+        assert_eq!(buf.node_count(), 2);
+        buf.fill_varint_slot(varint, 4);
+        buf.fill_placeholder(placeholder, Bytes::from_static(b"ph"));
+        {
+            let mut node = buf.reserve_as_node(8);
+            node.extend_from_slice(b"node");
+        }
+        assert_eq!(&buf.concat()[..], b"ph\x04bodynode");
+        assert_eq!(counter.load(Ordering::Relaxed), 7);
+        buf.verify_invariants();
+    }
+
+    fn write_header<W: std::io::Write>(mut writer: W, len: usize) -> std::io::Result<()> {
+        write!(writer, "Content-Length: {len}\r\n")
+    }
+
+    #[test]
+    fn io_write_through_mut_ref() {
+        use std::io::Write as _;
+
+        let mut buf = LinkedBytes::new();
+        write_header(&mut buf, 42).unwrap();
+        let host = "example.com";
+        write!(&mut buf, "Host: {host}\r\n").unwrap();
+        assert!(buf == "Content-Length: 42\r\nHost: example.com\r\n");
+    }
+
+    #[test]
+    fn write_error_reports_position() {
+        let mut buf = mixed();
+        let mut writer = MockVectoredWriter::new([
+            Step::Accept(9),
+            Step::Fail(std::io::ErrorKind::ConnectionReset),
+        ]);
+        let e = buf
+            .sync_write_all_vectored_detailed(&mut writer)
+            .unwrap_err();
+        assert_eq!(e.bytes_written(), 9);
+        // "head-" is written, and 4 bytes of "bytes-"
+        assert_eq!(e.last_chunk_index(), 1);
+        assert_eq!(buf.chunks_covered_by(e.bytes_written()), (1, 4));
+        assert_eq!(e.error().kind(), std::io::ErrorKind::ConnectionReset);
+        assert!(e.to_string().ends_with("(after 9 bytes, at chunk 1)"));
+        let e: std::io::Error = e.into();
+        assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset);
+
+        // the simple method returns the plain io error
+        let mut buf = mixed();
+        let mut writer = MockVectoredWriter::new([
+            Step::Accept(9),
+            Step::Fail(std::io::ErrorKind::ConnectionReset),
+        ]);
+        let e = buf.sync_write_all_vectored(&mut writer).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn swap_trailing_buffer_keeps_content() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"first-");
+        let old = buf.swap_trailing_buffer(BytesMut::with_capacity(64));
+        assert!(old.is_empty());
+        assert!(old.capacity() > 0);
+        buf.put_slice(b"second-");
+        let spare = buf.swap_trailing_buffer(old);
+        // the content written into the spare buffer is sealed into the list
+        assert!(spare.is_empty());
+        assert_eq!(spare.capacity(), 64 - 7);
+        buf.put_slice(b"third");
+        assert!(buf == "first-second-third");
+        assert_eq!(buf.chunks_count(), 3);
+        buf.verify_invariants();
+        buf.reset();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn consume_front_nodes_frees_flushed_nodes() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"aa");
+        buf.insert(Bytes::from_static(b"BB"));
+        buf.put_slice(b"cc");
+        buf.insert(Bytes::from_static(b"DD"));
+        buf.put_slice(b"tail");
+        assert_eq!(buf.node_count(), 4);
+
+        buf.consume_front_nodes(2);
+        assert!(buf == "ccDDtail");
+        assert_eq!(buf.len(), 8);
+        buf.put_slice(b"-more");
+        buf.consume_front_nodes(100);
+        assert_eq!(buf.node_count(), 0);
+        assert!(buf == "tail-more");
+        buf.verify_invariants();
+
+        buf.reset();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn from_conversions() {
+        let mut buf = LinkedBytes::from(Bytes::from_static(b"bytes"));
+        assert_eq!((buf.len(), buf.node_count()), (5, 1));
+        assert!(buf.is_all_zero_copy());
+        buf.reset();
+        assert!(buf.is_empty());
+
+        let mut buf = LinkedBytes::from(&b"static"[..]);
+        assert_eq!((buf.len(), buf.node_count()), (6, 1));
+        assert!(buf.iter_list().all(|node| matches!(node, Node::Static(_))));
+        buf.reset();
+        assert!(buf.is_empty());
+
+        let vec = b"vec".to_vec();
+        let ptr = vec.as_ptr();
+        let mut buf = LinkedBytes::from(vec);
+        assert_eq!((buf.len(), buf.node_count()), (3, 0));
+        // owned as the bytes_mut without copying
+        assert_eq!(buf.bytes().as_ptr(), ptr);
+        buf.reset();
+        assert!(buf.is_empty());
+
+        let mut buf = LinkedBytes::from(String::from("string"));
+        assert_eq!((buf.len(), buf.node_count()), (6, 0));
+        assert!(buf == "string");
+        buf.reset();
+        assert!(buf.is_empty());
+
+        let mut buf = LinkedBytes::from(BytesMut::from(&b"bytes_mut"[..]));
+        assert_eq!((buf.len(), buf.node_count()), (9, 0));
+        buf.reset();
+        assert!(buf.is_empty());
+        buf.put_slice(b"reused");
+        assert!(buf == "reused");
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored_reports_progress() {
+        let mut buf = mixed();
+        let mut writer =
+            MockVectoredWriter::new([Step::Accept(3), Step::Skip, Step::Accept(10), Step::All]);
+        let mut progress = Vec::new();
+        buf.write_all_vectored_with_progress(&mut writer, |written| progress.push(written))
+            .await
+            .unwrap();
+        assert_eq!(progress, [3, 13, buf.len()]);
+        assert!(progress.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(writer.data, buf.concat());
+    }
+
+    #[test]
+    fn chunk_apis_skip_empty_nodes() {
+        let mut buf = LinkedBytes::new();
+        // an empty split, a placeholder and an emptied node
+        let _ = buf.split();
+        let _ = buf.insert_placeholder();
+        buf.insert(Bytes::from_static(b"taken"));
+        buf.insert(Bytes::from_static(b"kept"));
+        let _ = buf.take_bytes(5);
+        buf.put_slice(b"tail");
+
+        // structural APIs count every node
+        assert_eq!(buf.node_count(), 4);
+        assert_eq!(buf.iter_list().count(), 4);
+        // chunk-facing APIs skip the empty ones
+        assert_eq!(buf.chunks_count(), 2);
+        assert_eq!(buf.iter_chunks().count(), 2);
+        assert_eq!(buf.io_slice().len(), 2);
+        assert_eq!(buf.io_slice_limited(usize::MAX).0.len(), 2);
+        assert!(buf.iter_chunks().all(|chunk| !chunk.is_empty()));
+        assert!(buf == "kepttail");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn front_slot_header() {
+        let mut buf = LinkedBytes::new();
+        let handle = buf.reserve_front_slot(12);
+        buf.put_slice(b"body");
+        buf.insert(Bytes::from_static(b"-more"));
+        let body_len = buf.len() - 12;
+
+        let mut header = [0u8; 12];
+        header[..4].copy_from_slice(b"MAGI");
+        header[4..8].copy_from_slice(&(body_len as u32).to_be_bytes());
+        header[8..].copy_from_slice(&7u32.to_be_bytes());
+        buf.fill_front_slot(handle, &header);
+
+        assert_eq!(&buf.io_slice()[0][..], header);
+        assert_eq!(&buf.concat()[..], [&header[..], b"body-more"].concat());
+        buf.verify_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "data length doesn't match the front slot")]
+    fn front_slot_length_mismatch() {
+        let mut buf = LinkedBytes::new();
+        let handle = buf.reserve_front_slot(4);
+        buf.fill_front_slot(handle, b"HDR");
+    }
+
+    #[test]
+    fn ffi_parts_of_contiguous_content() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"contiguous");
+        let (ptr, len) = buf.as_ffi_parts().unwrap();
+        assert_eq!((ptr, len), (buf.bytes().as_ptr(), 10));
+        // SAFETY: `buf` is borrowed and not mutated while the slice is alive
+        let slice = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert_eq!(slice, b"contiguous");
+
+        let buf = LinkedBytes::from(Bytes::from_static(b"single"));
+        let (ptr, len) = buf.as_ffi_parts().unwrap();
+        assert_eq!((ptr, len), (b"single".as_ptr(), 6));
+
+        assert_eq!(
+            LinkedBytes::empty().as_ffi_parts().map(|(_, len)| len),
+            Some(0)
+        );
+        assert_eq!(mixed().as_ffi_parts(), None);
+    }
+
+    #[test]
+    fn node_pool_reuses_allocations() {
+        let mut buf = LinkedBytes::new();
+        buf.set_node_pool_size(2);
+        let _ = buf.reserve_front_slot(16);
+        let slot = buf.iter_list().next().unwrap().as_ref().as_ptr();
+        for _ in 0..100 {
+            buf.clear();
+            assert_eq!(buf.node_pool.len(), 1);
+            let handle = buf.reserve_front_slot(16);
+            buf.put_slice(b"body");
+            // the freed slot is taken back from the pool instead of allocating
+            assert_eq!(buf.iter_list().next().unwrap().as_ref().as_ptr(), slot);
+            buf.fill_front_slot(handle, &[1; 16]);
+            buf.verify_invariants();
+        }
+
+        // without the pool, a new buffer is allocated for the slot
+        buf.set_node_pool_size(0);
+        buf.clear();
+        assert!(buf.node_pool.is_empty());
+    }
+
+    #[test]
+    fn subslice_copy_windows() {
+        let buf = mixed();
+        let content = buf.concat();
+        // within one node
+        assert_eq!(buf.subslice_copy(1..4), b"ead");
+        assert_eq!(buf.subslice_copy(5..11), b"bytes-");
+        // spanning several nodes
+        assert_eq!(buf.subslice_copy(3..20), &content[3..20]);
+        assert_eq!(buf.subslice_copy(0..buf.len()), &content[..]);
+        assert!(buf.subslice_copy(7..7).is_empty());
+        for start in 0..buf.len() {
+            for end in start..=buf.len() {
+                assert_eq!(buf.subslice_copy(start..end), &content[start..end]);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "is out of bounds")]
+    fn subslice_copy_out_of_range() {
+        let buf = mixed();
+        let _ = buf.subslice_copy(0..buf.len() + 1);
+    }
+
+    #[test]
+    fn fold_chunks_len_and_sum() {
+        let buf = mixed();
+        assert_eq!(
+            buf.fold_chunks(0, |len, chunk| len + chunk.len()),
+            buf.len()
+        );
+        let sum = buf.fold_chunks(0u64, |sum, chunk| {
+            sum + chunk.iter().map(|&b| b as u64).sum::<u64>()
+        });
+        assert_eq!(sum, buf.concat().iter().map(|&b| b as u64).sum::<u64>());
+        assert_eq!(LinkedBytes::new().fold_chunks(0, |n, _| n + 1), 0);
+    }
+
+    #[test]
+    fn contiguous_mode_keeps_one_chunk() {
+        let mut buf = LinkedBytes::new();
+        buf.set_contiguous_mode(true);
+        buf.put_slice(b"head-");
+        buf.insert(Bytes::from_static(b"bytes-"));
+        buf.insert_faststr(FastStr::from_static_str("faststr-"));
+        buf.insert_static(b"static-");
+        buf.insert_bytes_mut(BytesMut::from(&b"mut"[..]));
+        assert_eq!(buf.chunks_count(), 1);
+        assert_eq!(buf.node_count(), 0);
+        assert!(buf == "head-bytes-faststr-static-mut");
+
+        buf.set_contiguous_mode(false);
+        buf.insert(Bytes::from_static(b"!"));
+        assert_eq!(buf.chunks_count(), 2);
+    }
+
+    #[test]
+    fn byte_histogram_counts() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"aab");
+        buf.insert(Bytes::from_static(b"bcc"));
+        buf.put_slice(&[0, 255, 255]);
+        let histogram = buf.byte_histogram();
+        assert_eq!(histogram[b'a' as usize], 2);
+        assert_eq!(histogram[b'b' as usize], 2);
+        assert_eq!(histogram[b'c' as usize], 2);
+        assert_eq!(histogram[0], 1);
+        assert_eq!(histogram[255], 2);
+        assert_eq!(histogram.iter().sum::<u64>(), buf.len() as u64);
+    }
+
+    #[test]
+    fn reset_reclaims_the_split_allocation() {
+        let mut buf = LinkedBytes::with_capacity(1024);
+        let ptr = buf.bytes().as_ptr();
+        for _ in 0..10 {
+            buf.put_slice(b"head-");
+            buf.insert(Bytes::from_static(b"bytes-"));
+            buf.put_slice(b"mid-");
+            buf.insert_static(b"static-");
+            buf.put_slice(b"tail");
+            assert!(buf == "head-bytes-mid-static-tail");
+            // the nodes and the bytes_mut are merged back into the original allocation
+            let report = buf.reset_with_report();
+            assert!(report.reused);
+            assert_eq!(buf.bytes().as_ptr(), ptr);
+            assert_eq!(buf.capacity(), 1024);
+            assert!(buf.is_empty());
+            assert_eq!(buf.node_count(), 0);
+        }
+    }
+
+    #[test]
+    fn reset_with_empty_and_full_buffers() {
+        let mut buf = LinkedBytes::new();
+        buf.reset();
+        assert!(buf.is_empty());
+
+        let mut buf = LinkedBytes::with_capacity(8);
+        buf.put_slice(b"12345678");
+        buf.insert(Bytes::from_static(b"!"));
+        buf.reset();
+        assert_eq!(buf.capacity(), 8);
+        buf.put_slice(b"reused");
+        assert!(buf == "reused");
+    }
+
+    #[test]
+    fn snapshot_is_unchanged_by_later_writes() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"head-");
+        buf.insert(Bytes::from_static(b"bytes-"));
+        buf.put_slice(b"tail");
+        let snapshot = buf.snapshot();
+        assert_eq!(&snapshot[..], b"head-bytes-tail");
+
+        buf.put_slice(b"-more");
+        buf.insert(Bytes::from_static(b"-bytes"));
+        assert_eq!(&snapshot[..], b"head-bytes-tail");
+        assert!(buf == "head-bytes-tail-more-bytes");
+        // the prefix shares the memory of the snapshot
+        assert_eq!(
+            buf.iter_chunks().next().unwrap().as_ptr(),
+            snapshot.as_ptr()
+        );
+        buf.verify_invariants();
+
+        // taking another snapshot of a frozen buffer doesn't copy
+        let mut frozen = LinkedBytes::from(snapshot.clone());
+        assert_eq!(frozen.snapshot().as_ptr(), snapshot.as_ptr());
+    }
+
+    #[test]
+    fn rebalance_evens_out_chunk_sizes() {
+        static LARGE: [u8; 100] = [7; 100];
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"ab");
+        buf.insert(Bytes::from_static(&LARGE));
+        buf.put_slice(b"c");
+        buf.insert_static(b"d");
+        buf.put_slice(&[9; 45]);
+        let content = buf.concat();
+        assert_eq!(buf.min_chunk_len(), 1);
+
+        buf.rebalance(16);
+        assert_eq!(buf.concat(), content);
+        let lens: Vec<_> = buf.iter_chunks().map(<[u8]>::len).collect();
+        let (last, rest) = lens.split_last().unwrap();
+        assert!(rest.iter().all(|&len| len == 16), "{lens:?}");
+        assert!(*last <= 16);
+        assert_eq!(buf.max_chunk_len(), 16);
+        assert_eq!(buf.count_small_chunks(16), 1);
+        buf.verify_invariants();
+
+        buf.put_slice(b"after");
+        assert_eq!(buf.len(), content.len() + 5);
+        buf.reset();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn rebalance_with_target_larger_than_len() {
+        for target in [64, usize::MAX] {
+            let mut buf = LinkedBytes::new();
+            buf.put_slice(b"abcd");
+            buf.insert_static(b"efgh");
+            buf.put_slice(b"ijkl");
+            buf.rebalance(target);
+            assert!(buf == "abcdefghijkl");
+            assert_eq!(buf.chunks_count(), 1);
+            // only the content left is reserved, not the target
+            assert!(matches!(&buf.list[0], Node::BytesMut(b) if b.capacity() == 12));
+            buf.verify_invariants();
+        }
+    }
+
+    #[test]
+    fn vectored_writes_are_capped_to_iov_max() {
+        let mut buf = LinkedBytes::new();
+        for i in 0..3000u32 {
+            buf.insert(Bytes::copy_from_slice(&i.to_be_bytes()));
+        }
+        assert_eq!(buf.chunks_count(), 3000);
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.data, buf.concat());
+        let counts: Vec<_> = writer.vectored_calls.iter().map(Vec::len).collect();
+        assert_eq!(counts, [MAX_IOVS_PER_WRITE, MAX_IOVS_PER_WRITE, 952]);
+    }
+
+    #[test]
+    // sockets aren't supported by Miri
+    #[cfg_attr(miri, ignore)]
+    fn sync_write_all_vectored_to_loopback_socket() {
+        use std::io::Read as _;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let reader = std::thread::spawn(move || {
+            let mut data = Vec::new();
+            listener.accept().unwrap().0.read_to_end(&mut data).unwrap();
+            data
+        });
+
+        // large enough for partial writes, with more io slices than a single call takes
+        let mut buf = LinkedBytes::new();
+        for i in 0..4000u32 {
+            buf.put_slice(&i.to_le_bytes());
+            buf.insert(Bytes::from(vec![i as u8; 1024]));
+        }
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        buf.sync_write_all_vectored(&mut stream).unwrap();
+        drop(stream);
+        assert_eq!(reader.join().unwrap(), buf.concat());
+    }
+
+    #[test]
+    fn zero_copy_audit() {
+        let mut buf = LinkedBytes::new();
+        assert!(buf.is_all_zero_copy());
+        buf.insert(Bytes::from_static(b"bytes"));
+        buf.insert_static(b"static");
+        buf.insert_faststr(FastStr::from_static_str("faststr"));
+        assert!(buf.is_all_zero_copy());
+        assert_eq!(buf.shared_chunk_count(), 2);
+
+        buf.put_slice(b"copied");
+        assert!(!buf.is_all_zero_copy());
+        // still not after the copied bytes are sealed into the list
+        buf.insert(Bytes::from_static(b"bytes"));
+        assert!(!buf.is_all_zero_copy());
+    }
+
+    #[test]
+    fn varint_slot_lengths() {
+        for (body_len, varint) in [
+            (0, &[0x00][..]),
+            (1, &[0x01]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (300, &[0xac, 0x02]),
+            (16384, &[0x80, 0x80, 0x01]),
+        ] {
+            let mut buf = LinkedBytes::new();
+            buf.put_slice(b"tag");
+            let handle = buf.reserve_varint_slot();
+            buf.put_slice(&vec![b'x'; body_len]);
+            assert_eq!(buf.varint_slot_body_len(handle), body_len);
+            buf.fill_varint_slot(handle, body_len as u64);
+            assert_eq!(buf.len(), 3 + varint.len() + body_len);
+            let content = buf.concat();
+            assert_eq!(&content[3..3 + varint.len()], varint, "{body_len}");
+            assert!(content[3 + varint.len()..].iter().all(|&b| b == b'x'));
+            buf.verify_invariants();
+        }
+
+        let mut buf = LinkedBytes::new();
+        let handle = buf.reserve_varint_slot();
+        buf.fill_varint_slot(handle, u64::MAX);
+        assert_eq!(buf.len(), MAX_VARINT_LEN);
+    }
+
+    #[test]
+    #[should_panic(expected = "varint slot handle is invalidated")]
+    fn varint_slot_filled_twice() {
+        let mut buf = LinkedBytes::new();
+        let handle = buf.reserve_varint_slot();
+        buf.put_slice(b"body");
+        buf.fill_varint_slot(handle, 4);
+        buf.fill_varint_slot(handle, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "varint slot handle is invalidated")]
+    fn varint_slot_handle_used_after_reset() {
+        let mut buf = LinkedBytes::new();
+        let stale = buf.reserve_varint_slot();
+        buf.put_slice(b"body");
+        buf.reset();
+        // a new slot at the same index must not be filled by the stale handle
+        let _ = buf.reserve_varint_slot();
+        buf.fill_varint_slot(stale, 4);
+    }
+
+    #[test]
+    fn varint_slot_survives_front_removals() {
+        let mut buf = LinkedBytes::new();
+        buf.insert_static(b"flushed");
+        buf.put_slice(b"a");
+        let first = buf.reserve_varint_slot();
+        buf.put_slice(b"b");
+        let second = buf.reserve_varint_slot();
+        buf.put_slice(b"c");
+        buf.consume_front_nodes(1);
+        buf.insert_front(Bytes::from_static(b"<"));
+        buf.advance(1);
+        buf.fill_varint_slot(second, 2);
+        buf.fill_varint_slot(first, 1);
+        assert!(buf == "a\x01b\x02c");
+        buf.verify_invariants();
+    }
+
+    /// A subscriber recording the `bytes` field of every event.
+    #[cfg(feature = "tracing")]
+    #[derive(Default)]
+    struct BytesRecorder(std::sync::Mutex<Vec<u64>>);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for BytesRecorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Visitor<'a>(&'a mut Vec<u64>);
+
+            impl tracing::field::Visit for Visitor<'_> {
+                fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+                    if field.name() == "bytes" {
+                        self.0.push(value);
+                    }
+                }
+
+                fn record_debug(&mut self, _: &tracing::field::Field, _: &dyn std::fmt::Debug) {}
+            }
+
+            event.record(&mut Visitor(&mut self.0.lock().unwrap()));
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_records_written_bytes() {
+        let recorder = Arc::new(BytesRecorder::default());
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let mut buf = mixed();
+            let mut writer = MockVectoredWriter::new([Step::Accept(3), Step::All]);
+            buf.sync_write_all_vectored(&mut writer).unwrap();
+
+            let mut writer = MockVectoredWriter::new([
+                Step::Accept(9),
+                Step::Fail(std::io::ErrorKind::BrokenPipe),
+            ]);
+            buf.sync_write_all_vectored(&mut writer).unwrap_err();
+
+            // the fast path of a lone bytes_mut is traced as well
+            let mut buf = LinkedBytes::new();
+            buf.put_slice(b"fast");
+            let mut out = Vec::new();
+            buf.sync_write_all_vectored(&mut out).unwrap();
+        });
+        assert_eq!(*recorder.0.lock().unwrap(), [mixed().len() as u64, 9, 4]);
+    }
+
+    #[test]
+    fn concat_into_appends_buffers() {
+        let mut out = BytesMut::from(&b">"[..]);
+        mixed().concat_into(&mut out);
+        let mut other = LinkedBytes::new();
+        other.insert(Bytes::from_static(b"|other"));
+        other.put_slice(b"-tail");
+        other.concat_into(&mut out);
+        assert_eq!(&out[..], b">head-bytes-static-mid-faststr-tail|other-tail");
+
+        LinkedBytes::new().concat_into(&mut out);
+        assert_eq!(out.len(), 1 + mixed().len() + 11);
+    }
+
+    #[test]
+    fn node_as_str() {
+        assert_eq!(
+            Node::FastStr(FastStr::from_static_str("faststr")).as_str(),
+            Some("faststr")
+        );
+        assert_eq!(
+            Node::Bytes(Bytes::from_static(b"bytes")).as_str(),
+            Some("bytes")
+        );
+        assert_eq!(
+            Node::BytesMut(BytesMut::from(&b"bytes_mut"[..])).as_str(),
+            Some("bytes_mut")
+        );
+        assert_eq!(Node::Static(b"static").as_str(), Some("static"));
+        assert_eq!(Node::Bytes(Bytes::from_static(b"\xff\xfe")).as_str(), None);
+        assert_eq!(Node::Static(b"\xc3").as_str(), None);
+        assert_eq!(Node::BytesMut(BytesMut::new()).as_str(), Some(""));
+    }
+
+    #[test]
+    fn recommended_capacity_rounds_to_pages() {
+        assert_eq!(LinkedBytes::recommended_capacity(0), 0);
+        assert_eq!(LinkedBytes::recommended_capacity(100), 100);
+        assert_eq!(LinkedBytes::recommended_capacity(4096), 4096);
+        assert_eq!(LinkedBytes::recommended_capacity(4097), 8192);
+        assert_eq!(LinkedBytes::recommended_capacity(8200), 12288);
+        assert_eq!(LinkedBytes::recommended_capacity(usize::MAX), usize::MAX);
+
+        assert_eq!(LinkedBytes::with_capacity(8200).capacity(), 12288);
+        assert_eq!(LinkedBytes::with_capacity(100).capacity(), 100);
+    }
+
+    #[tokio::test]
+    async fn prepend_static_leads_the_io_slices() {
+        let mut buf = mixed();
+        buf.prepend_static(b"MAGIC");
+        assert_eq!(&buf.io_slice()[0][..], b"MAGIC");
+        assert_eq!(buf.iter_chunks().next(), Some(&b"MAGIC"[..]));
+
+        let mut writer = RecordingWriter::default();
+        buf.write_all_vectored(&mut writer).await.unwrap();
+        assert_eq!(writer.vectored_calls[0][0], 5);
+        assert!(writer.data.starts_with(b"MAGIChead-"));
+        assert_eq!(writer.data, buf.concat());
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn reset_after_the_trailing_buffer_reallocates() {
+        let mut buf = LinkedBytes::with_capacity(64);
+        let ptr = buf.bytes().as_ptr();
+        buf.put_slice(b"head-");
+        buf.insert(Bytes::from_static(b"bytes-"));
+        // grow the bytes_mut split off by `insert` beyond the original allocation
+        buf.put_slice(&[b'x'; 4096]);
+        assert_ne!(buf.bytes().as_ptr(), unsafe { ptr.add(5) });
+
+        let report = buf.reset_with_report();
+        assert!(!report.reused);
+        assert!(buf.is_empty());
+        assert_eq!(buf.node_count(), 0);
+        // the larger buffer is kept
+        assert!(buf.capacity() >= 4096);
+        buf.put_slice(b"reused");
+        buf.insert(Bytes::from_static(b"-again"));
+        assert!(buf == "reused-again");
+        assert!(buf.reset_with_report().reused);
+    }
+
+    #[test]
+    fn count_small_chunks_of_mixed() {
+        let buf = mixed();
+        // "head-" | "bytes-" | "static-" | "mid-" | "faststr-" | "tail"
+        assert_eq!(buf.count_small_chunks(0), 0);
+        assert_eq!(buf.count_small_chunks(5), 2);
+        assert_eq!(buf.count_small_chunks(7), 4);
+        assert_eq!(buf.count_small_chunks(100), 6);
+        assert_eq!(LinkedBytes::new().count_small_chunks(100), 0);
+    }
+
+    #[test]
+    fn into_parts_round_trip() {
+        #[cfg_attr(feature = "safe-only", allow(unused_mut))]
+        let mut buf = mixed();
+        #[cfg(not(feature = "safe-only"))]
+        let _ = buf.io_slice_cached();
+        let content = buf.concat();
+        let (list, bytes) = buf.into_parts();
+        assert_eq!(list.len(), 5);
+        assert_eq!(&bytes[..], b"tail");
+
+        let mut buf = LinkedBytes::from_parts(list, bytes);
+        #[cfg(not(feature = "safe-only"))]
+        {
+            assert!(buf.ioslice.is_empty());
+            assert!(buf.io_slice_cache.is_empty());
+        }
+        assert_eq!(buf.concat(), content);
+        assert_eq!(buf.len(), content.len());
+        buf.verify_invariants();
+
+        // the io slices are prepared from scratch
+        let mut writer = MockVectoredWriter::new([Step::Accept(3), Step::All]);
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.data, content);
+
+        // the `BytesMut` nodes split from the bytes_mut are reclaimed
+        assert!(buf.reset_with_report().reused);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn write_all_vectored_across_threads() {
+        fn assert_send<T: Send>(t: T) -> T {
+            t
+        }
+
+        for script in scripts() {
+            let handle = tokio::spawn(async move {
+                let mut buf = mixed();
+                let mut writer = MockVectoredWriter::new(script);
+                // each `Skip` yields, so the task may be resumed on another thread
+                assert_send(buf.write_all_vectored(&mut writer))
+                    .await
+                    .unwrap();
+                assert_eq!(writer.data, buf.concat());
+                buf.verify_invariants();
+            });
+            handle.await.unwrap();
+        }
+    }
+
+    #[test]
+    fn reserve_as_node_is_a_distinct_chunk() {
+        const LEN: usize = 1024 * 1024;
+        let mut buf = LinkedBytes::with_capacity(64);
+        buf.put_slice(b"head");
+        {
+            let mut node = buf.reserve_as_node(LEN);
+            assert!(node.capacity() >= LEN);
+            node.put_bytes(7, LEN);
+        }
+        buf.put_slice(b"tail");
+
+        let chunks: Vec<_> = buf.iter_chunks().map(<[u8]>::len).collect();
+        assert_eq!(chunks, [4, LEN, 4]);
+        // the bytes_mut is not grown
+        assert!(buf.bytes().capacity() <= 64);
+        assert!(buf.iter_chunks().nth(1).unwrap().iter().all(|&b| b == 7));
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn eq_str_across_nodes() {
+        let buf = mixed();
+        let expected = "head-bytes-static-mid-faststr-tail";
+        assert!(buf == *expected);
+        assert!(buf == expected);
+        let owned = String::from(expected);
+        assert!(buf == owned);
+        assert!(buf != "head-bytes-static-mid-faststr-tai");
+        assert!(buf != "head-bytes-static-mid-faststr-tail!");
+        assert!(buf != "head-bytes-STATIC-mid-faststr-tail");
+        assert!(LinkedBytes::new() == String::new());
+        assert!(LinkedBytes::new() != "a");
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored_through_boxed_writer() {
+        let mut buf = mixed();
+        let mut writer: Box<dyn AsyncWrite + Unpin + Send> = Box::new(MockVectoredWriter::new([
+            Step::Accept(3),
+            Step::Skip,
+            Step::Accept(10),
+        ]));
+        buf.write_all_vectored_dyn(&mut *writer).await.unwrap();
+
+        let mut writer: Box<dyn AsyncWrite + Unpin + Send> = Box::new(MockVectoredWriter::new([
+            Step::Accept(3),
+            Step::Fail(std::io::ErrorKind::BrokenPipe),
+        ]));
+        let e = buf.write_all_vectored_dyn(&mut *writer).await.unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn compact_to_single_allocation_right_sizes() {
+        let mut buf = mixed();
+        buf.reserve(4096);
+        let content = buf.concat();
+        buf.compact_to_single_allocation();
+        assert_eq!(buf.chunks_count(), 1);
+        assert_eq!(buf.node_count(), 0);
+        assert_eq!(buf.capacity(), buf.len());
+        assert_eq!(buf.concat(), content);
+        buf.verify_invariants();
+
+        buf.put_slice(b"-more");
+        assert_eq!(buf.len(), content.len() + 5);
+    }
+
+    /// A writer accepting the first `accept` bytes, then never ready again.
+    struct StalledWriter {
+        accept: usize,
+        data: Vec<u8>,
+    }
+
+    impl AsyncWrite for StalledWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            let this = self.get_mut();
+            if this.accept == 0 {
+                return Poll::Pending;
+            }
+            let n = this.accept.min(buf.len());
+            this.accept -= n;
+            this.data.extend_from_slice(&buf[..n]);
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored_deadline_times_out_and_resumes() {
+        let mut buf = mixed();
+        let content = buf.concat();
+        let mut writer = StalledWriter {
+            accept: 7,
+            data: Vec::new(),
+        };
+        let deadline = Instant::now() + std::time::Duration::from_millis(10);
+        let e = buf
+            .write_all_vectored_deadline(&mut writer, deadline)
+            .await
+            .unwrap_err();
+        assert_eq!(e.error().kind(), std::io::ErrorKind::TimedOut);
+        assert_eq!(e.bytes_written(), 7);
+        // the written bytes are removed, so a retry resumes
+        assert_eq!(buf.concat(), content[7..]);
+        buf.verify_invariants();
+
+        let deadline = Instant::now() + std::time::Duration::from_secs(10);
+        buf.write_all_vectored_deadline(&mut writer.data, deadline)
+            .await
+            .unwrap();
+        assert_eq!(writer.data, content);
+    }
+
+    #[test]
+    fn replace_node_swaps_content() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"head-");
+        buf.insert(Bytes::from_static(b"????"));
+        buf.put_slice(b"-tail");
+        let old = buf.replace_node(1, Node::Bytes(Bytes::from_static(b"body-content")));
+        assert!(matches!(old, Some(Node::Bytes(b)) if b == "????"));
+        assert!(buf == "head-body-content-tail");
+        assert_eq!(buf.len(), 22);
+        buf.verify_invariants();
+
+        assert!(buf.replace_node(2, Node::Static(b"x")).is_none());
+        assert!(buf == "head-body-content-tail");
+        buf.reset();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn writable_chunk_len_is_the_spare_capacity() {
+        let mut buf = LinkedBytes::with_capacity(64);
+        assert_eq!(buf.writable_chunk_len(), 64);
+        buf.put_slice(b"head");
+        assert_eq!(buf.writable_chunk_len(), buf.capacity() - buf.bytes().len());
+        assert_eq!(buf.writable_chunk_len(), 60);
+        assert_eq!(buf.chunk_mut().len(), 60);
+        buf.insert(Bytes::from_static(b"bytes"));
+        assert_eq!(buf.writable_chunk_len(), buf.capacity() - buf.bytes().len());
+        buf.put_bytes(0, 60);
+        assert_eq!(buf.writable_chunk_len(), 0);
+    }
+
+    #[test]
+    fn reset_report_reuse_and_realloc() {
+        let mut buf = LinkedBytes::with_capacity(64);
+        buf.put_slice(b"head-");
+        buf.insert(Bytes::from_static(b"bytes-"));
+        buf.put_slice(b"tail");
+        let report = buf.reset_with_report();
+        assert!(report.reused);
+        assert_eq!(report.reclaimed_bytes, 64);
+
+        // the bytes_mut split off by `insert` is grown into a new allocation
+        buf.put_slice(b"head-");
+        buf.insert(Bytes::from_static(b"bytes-"));
+        buf.put_slice(&[0; 1000]);
+        let report = buf.reset_with_report();
+        assert!(!report.reused);
+        assert_eq!(report.reclaimed_bytes, buf.capacity());
+        assert!(report.reclaimed_bytes >= 1000);
+    }
+
+    #[test]
+    fn from_buf_sources() {
+        let large = Bytes::from(vec![1; 2048]);
+        let buf = LinkedBytes::from_buf(large.clone());
+        assert_eq!(buf.concat(), large);
+        // inserted without copying
+        assert_eq!(buf.iter_chunks().next().unwrap().as_ptr(), large.as_ptr());
+
+        let small = Bytes::from_static(b"small");
+        let buf = LinkedBytes::from_buf(small.clone().chain(large.clone()));
+        assert_eq!(buf.len(), 5 + 2048);
+        assert_eq!(buf.chunks_count(), 2);
+        assert_eq!(&buf.concat()[..5], b"small");
+        assert!(buf
+            .iter_chunks()
+            .any(|chunk| chunk.as_ptr() == large.as_ptr()));
+
+        let mut deque: VecDeque<u8> = (0..=255).collect();
+        deque.rotate_left(100);
+        let expected: Vec<u8> = deque.iter().copied().collect();
+        let buf = LinkedBytes::from_buf(deque);
+        assert_eq!(&buf.concat()[..], expected);
+        assert_eq!(buf.node_count(), 0);
+    }
+
+    #[test]
+    fn operations_dont_leave_empty_zero_copy_nodes() {
+        let mut buf = LinkedBytes::new();
+        buf.insert(Bytes::new());
+        buf.insert_static(b"");
+        buf.insert_faststr(FastStr::empty());
+        buf.insert_front(Bytes::new());
+        buf.prepend_many([Bytes::new(), Bytes::from_static(b"a")]);
+        assert_eq!(buf.node_count(), 1);
+        buf.verify_invariants();
+
+        let mut buf = mixed();
+        buf.advance(5 + 6);
+        buf.verify_invariants();
+        let _ = buf.take_bytes(7);
+        buf.verify_invariants();
+        buf.consume_front_nodes(1);
+        buf.verify_invariants();
+        buf.truncate_nodes(1);
+        buf.verify_invariants();
+        let _ = buf.split_off_at_chunk(0);
+        buf.verify_invariants();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn prepend_many_keeps_order() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"body");
+        buf.prepend_many(
+            ["a", "b", "c"]
+                .into_iter()
+                .map(|s| Bytes::from_static(s.as_bytes())),
+        );
+        assert!(buf == "abcbody");
+        buf.prepend_many([Bytes::from_static(b"0"), Bytes::from_static(b"1")]);
+        assert!(buf == "01abcbody");
+        assert_eq!(buf.front_count, 5);
+        buf.verify_invariants();
+    }
+
+    #[test]
+    #[cfg(not(feature = "safe-only"))]
+    fn io_slice_cache_invalidation() {
+        let mut buf = mixed();
+        let first = buf.io_slice_cached().as_ptr() as usize;
+        let key = buf.io_slice_cache_key;
+        assert!(key.is_some());
+        // not rebuilt without a mutation
+        assert_eq!(buf.io_slice_cached().as_ptr() as usize, first);
+        assert_eq!(buf.io_slice_cache_key, key);
+        assert_eq!(buf.io_slice_cached().len(), 6);
+
+        // a mutation of the list invalidates it
+        buf.insert(Bytes::from_static(b"-more"));
+        assert_eq!(buf.io_slice_cached().len(), 7);
+        assert_ne!(buf.io_slice_cache_key, key);
+
+        // so does a write to the bytes_mut
+        let key = buf.io_slice_cache_key;
+        buf.put_slice(b"!");
+        assert_eq!(buf.io_slice_cached().len(), 8);
+        assert_ne!(buf.io_slice_cache_key, key);
+        let content: Vec<u8> = buf
+            .io_slice_cached()
+            .iter()
+            .flat_map(|s| s.iter().copied())
+            .collect();
+        assert_eq!(content, buf.concat());
+    }
+
+    #[test]
+    fn write_after_split_off_bytes_does_not_reallocate() {
+        let mut buf = LinkedBytes::with_capacity(64);
+        buf.put_slice(b"head-tail");
+        let tail = buf.split_off_bytes(5);
+        assert_eq!(&tail[..], b"tail");
+        assert!(buf.bytes.capacity() - buf.bytes.len() >= 64);
+        let ptr = buf.bytes.as_ptr();
+        buf.put_slice(&[b'x'; 32]);
+        assert_eq!(buf.bytes.as_ptr(), ptr);
+        assert_eq!(&buf.concat()[..5], b"head-");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn split_off_at_chunk_boundary() {
+        let mut buf = LinkedBytes::new();
+        for chunk in ["aa", "bb", "cc", "dd"] {
+            buf.insert(Bytes::from_static(chunk.as_bytes()));
+        }
+        buf.put_slice(b"ee");
+        assert_eq!(buf.chunks_count(), 5);
+
+        let tail = buf.split_off_at_chunk(2);
+        assert!(buf == "aabb");
+        assert!(tail == "ccddee");
+        assert_eq!(buf.chunks_count(), 2);
+        assert_eq!(tail.chunks_count(), 3);
+        assert!(tail.iter_chunks().next().unwrap().as_ptr() == b"cc".as_ptr());
+        buf.verify_invariants();
+        tail.verify_invariants();
+
+        let rest = buf.split_off_at_chunk(2);
+        assert!(rest.is_empty());
+        assert!(buf == "aabb");
+    }
+
+    #[test]
+    #[should_panic(expected = "split_off_at_chunk out of bounds")]
+    fn split_off_at_chunk_out_of_bounds() {
+        let mut buf = mixed();
+        let _ = buf.split_off_at_chunk(7);
+    }
+
+    #[test]
+    fn reset_infallible_with_pathological_nodes() {
+        let mut buf = LinkedBytes::new();
+        buf.insert_static(b"static-head");
+        buf.put_slice(b"a");
+        buf.insert_front(Bytes::from_static(b"front"));
+        buf.insert_bytes_mut(BytesMut::from(&b"foreign"[..]));
+        buf.put_slice(&[0; 10000]);
+        let _ = buf.insert_placeholder();
+        #[cfg(not(feature = "safe-only"))]
+        let _ = buf.io_slice_cached();
+
+        buf.reset_infallible();
+        assert!(buf.is_empty());
+        assert_eq!(buf.node_count(), 0);
+        buf.put_slice(b"usable");
+        buf.insert(Bytes::from_static(b"-again"));
+        assert!(buf == "usable-again");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn extend_bytes_exact_reserves_once() {
+        let chunks: Vec<Bytes> = (0..1000u32)
+            .map(|i| Bytes::copy_from_slice(&i.to_be_bytes()))
+            .collect();
+        let expected: Vec<u8> = chunks.iter().flatten().copied().collect();
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"head");
+        buf.extend_bytes_exact(chunks);
+        let capacity = buf.list.capacity();
+        assert!(capacity >= 1001);
+        assert_eq!(buf.node_count(), 1001);
+        assert_eq!(&buf.concat()[4..], expected);
+        assert_eq!(buf.list.capacity(), capacity);
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn empty_doesnt_allocate() {
+        let mut buf = LinkedBytes::empty();
+        assert_eq!(buf.capacity(), 0);
+        assert_eq!(buf.list.capacity(), 0);
+        assert!(buf.is_empty());
+        buf.reset();
+        assert_eq!(buf.capacity(), 0);
+
+        buf.put_slice(b"head");
+        buf.insert(Bytes::from_static(b"-bytes"));
+        assert!(buf == "head-bytes");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn try_freeze_single_outcomes() {
+        let bytes = Bytes::from_static(b"single");
+        let buf = LinkedBytes::from(bytes.clone());
+        let frozen = buf.try_freeze_single().ok().unwrap();
+        assert_eq!(frozen.as_ptr(), bytes.as_ptr());
+
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"trailing");
+        let ptr = buf.bytes().as_ptr();
+        let frozen = buf.try_freeze_single().ok().unwrap();
+        assert_eq!((&frozen[..], frozen.as_ptr()), (&b"trailing"[..], ptr));
+
+        assert!(LinkedBytes::new()
+            .try_freeze_single()
+            .ok()
+            .unwrap()
+            .is_empty());
+
+        let buf = mixed().try_freeze_single().err().unwrap();
+        assert!(buf == "head-bytes-static-mid-faststr-tail");
+    }
+
+    #[test]
+    fn buf_take_across_nodes() {
+        let mut buf = mixed();
+        let mut prefix = Vec::new();
+        {
+            let mut take = (&mut buf).take(13);
+            while take.has_remaining() {
+                let chunk = take.chunk();
+                prefix.extend_from_slice(chunk);
+                let len = chunk.len();
+                take.advance(len);
+            }
+        }
+        assert_eq!(prefix, b"head-bytes-st");
+        assert!(buf == "atic-mid-faststr-tail");
+
+        let bytes = buf.take_bytes(8);
+        assert_eq!(&bytes[..], b"atic-mid");
+        assert!(buf == "-faststr-tail");
+        buf.verify_invariants();
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored_tee_to_two_sinks() {
+        let buf = mixed();
+        let mut w1 = MockVectoredWriter::new([Step::Accept(3), Step::Skip, Step::Accept(7)]);
+        let mut w2 = RecordingWriter::default();
+        buf.write_all_vectored_tee(&mut w1, &mut w2).await.unwrap();
+        assert_eq!(w1.data, buf.concat());
+        assert_eq!(w2.data, buf.concat());
+        // the buffer is left as is
+        buf.write_all_vectored_tee(&mut w2, &mut w1).await.unwrap();
+        assert_eq!(w1.data.len(), 2 * buf.len());
+    }
+
+    #[test]
+    fn io_slice_keeps_adjacent_bytes_mut_chunks_apart() {
+        let mut buf = LinkedBytes::with_capacity(64);
+        buf.put_slice(b"aa");
+        // split off the bytes_mut without a node in between, the chunks are adjacent in memory
+        let _ = buf.split();
+        buf.put_slice(b"bb");
+        let chunks: Vec<&[u8]> = buf.iter_chunks().collect();
+        assert_eq!(chunks[0].as_ptr_range().end, chunks[1].as_ptr());
+        // a slice over both can't be borrowed from `&self`, only the write path merges them
+        let slices = buf.io_slice();
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[0].as_ptr(), chunks[0].as_ptr());
+        assert_eq!(slices[1].as_ptr(), chunks[1].as_ptr());
+    }
+
+    #[test]
+    #[cfg(not(feature = "safe-only"))]
+    fn write_all_vectored_merges_the_sealed_tail() {
+        let build = || {
+            let mut buf = LinkedBytes::with_capacity(64);
+            buf.put_slice(b"hdr");
+            buf.insert(Bytes::from_static(b"body"));
+            buf.put_slice(b"a");
+            buf.begin_frame();
+            buf.put_slice(b"bc");
+            buf
+        };
+        let mut buf = build();
+        assert_eq!(buf.chunks_count(), 4);
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.vectored_calls, [vec![3, 4, 3]]);
+        assert_eq!(writer.data, b"hdrbodyabc");
+
+        // partial writes advance within the merged io slice
+        let mut buf = build();
+        let mut writer = MockVectoredWriter::new([Step::Accept(8), Step::Accept(1), Step::All]);
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.data, b"hdrbodyabc");
+
+        // any change of the list separates them again
+        let mut buf = build();
+        buf.insert_static(b"!");
+        buf.put_slice(b"d");
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.vectored_calls, [vec![3, 4, 1, 2, 1, 1]]);
+
+        // so does a reallocation of the bytes_mut
+        let mut buf = LinkedBytes::with_capacity(8);
+        buf.put_slice(b"aa");
+        buf.begin_frame();
+        buf.put_slice(&[b'b'; 64]);
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.vectored_calls, [vec![2, 64]]);
+
+        // the bytes_mut keeps its allocation after the write
+        let mut buf = build();
+        let ptr = buf.bytes().as_ptr();
+        let capacity = buf.bytes().capacity();
+        buf.sync_write_all_vectored(&mut RecordingWriter::default())
+            .unwrap();
+        assert_eq!(buf.bytes().as_ptr(), ptr);
+        assert_eq!(buf.bytes().capacity(), capacity);
+        assert!(buf == "hdrbodyabc");
+        buf.put_slice(b"d");
+        assert!(buf == "hdrbodyabcd");
+        buf.verify_invariants();
+    }
+
+    #[test]
+    fn clone_into_reuses_dst() {
+        let mut dst = LinkedBytes::with_capacity(256);
+        let ptr = dst.bytes().as_ptr();
+        for _ in 0..2 {
+            let src = mixed();
+            src.clone_into(&mut dst);
+            assert!(dst == "head-bytes-static-mid-faststr-tail");
+            assert_eq!(dst.iter_chunks().next().unwrap().as_ptr(), ptr);
+            // the zero-copy nodes are shared
+            let shared = src.iter_chunks().nth(2).unwrap().as_ptr();
+            assert_eq!(dst.iter_chunks().nth(2).unwrap().as_ptr(), shared);
+            assert_eq!(dst.shared_chunk_count(), src.shared_chunk_count());
+            dst.verify_invariants();
+        }
+    }
+
+    #[test]
+    fn io_slice_limited_caps_the_count() {
+        let buf = mixed();
+        let (slices, len) = buf.io_slice_limited(3);
+        assert_eq!(slices.len(), 3);
+        assert_eq!(len, 5 + 6 + 7);
+        let (slices, len) = buf.io_slice_limited(100);
+        assert_eq!(slices.len(), 6);
+        assert_eq!(len, buf.len());
+        let (slices, len) = buf.io_slice_limited(0);
+        assert!(slices.is_empty());
+        assert_eq!(len, 0);
+    }
+
+    #[test]
+    fn randomized_operations_keep_invariants() {
+        static DATA: [u8; 256] = {
+            let mut data = [0; 256];
+            let mut i = 0;
+            while i < 256 {
+                data[i] = i as u8;
+                i += 1;
+            }
+            data
+        };
+        // xorshift, reproducible without extra dependencies
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move |n: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n as u64) as usize
+        };
+
+        for _ in 0..20 {
+            let mut buf = LinkedBytes::with_capacity(next(64));
+            let mut expected = Vec::new();
+            for _ in 0..200 {
+                let data = &DATA[..next(40)];
+                match next(12) {
+                    0 | 1 => {
+                        buf.put_slice(data);
+                        expected.extend_from_slice(data);
+                    }
+                    2 => {
+                        buf.insert(Bytes::from_static(data));
+                        expected.extend_from_slice(data);
+                    }
+                    3 => {
+                        buf.insert_static(data);
+                        expected.extend_from_slice(data);
+                    }
+                    4 => {
+                        buf.insert_front(Bytes::from_static(data));
+                        expected.splice(0..0, data.iter().copied());
+                    }
+                    5 => {
+                        let _ = buf.split();
+                    }
+                    6 => {
+                        let n = next(buf.len() + 1);
+                        buf.advance(n);
+                        expected.drain(..n);
+                    }
+                    7 => {
+                        let n = next(buf.len() + 1);
+                        let taken = buf.take_bytes(n);
+                        assert_eq!(taken, expected.drain(..n).collect::<Vec<_>>());
+                    }
+                    8 => {
+                        let count = next(buf.node_count() + 1);
+                        let removed: usize = buf
+                            .iter_list()
+                            .take(count)
+                            .map(|node| node.as_ref().len())
+                            .sum();
+                        buf.consume_front_nodes(count);
+                        expected.drain(..removed);
+                    }
+                    9 => {
+                        let at = next(buf.chunks_count() + 1);
+                        let tail = buf.split_off_at_chunk(at);
+                        tail.verify_invariants();
+                        expected.truncate(buf.len());
+                    }
+                    #[cfg(not(feature = "safe-only"))]
+                    10 => {
+                        let _ = buf.io_slice_cached();
+                    }
+                    _ => {
+                        buf.reset();
+                        expected.clear();
+                    }
+                }
+                buf.verify_invariants();
+                assert_eq!(&buf.concat()[..], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn large_put_slice_seals_instead_of_copying() {
+        let mut buf = LinkedBytes::with_capacity(SEAL_ON_GROW_LEN);
+        buf.put_slice(&vec![1; SEAL_ON_GROW_LEN]);
+        let ptr = buf.bytes().as_ptr();
+        buf.put_slice(&[2; 100]);
+        // the existing content is not moved, only the new data is copied
+        let chunks: Vec<&[u8]> = buf.iter_chunks().collect();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ptr(), ptr);
+        assert_eq!(chunks[0].len(), SEAL_ON_GROW_LEN);
+        assert_eq!(chunks[1], &[2; 100]);
+        buf.verify_invariants();
+
+        // smaller buffers grow as usual
+        let mut buf = LinkedBytes::with_capacity(16);
+        buf.put_slice(&[1; 16]);
+        buf.put_slice(&[2; 100]);
+        assert_eq!(buf.chunks_count(), 1);
+    }
+
+    #[test]
+    fn into_faststr_single_and_multi() {
+        let s = FastStr::from_string("a faststr long enough not to be inlined".to_string());
+        let mut buf = LinkedBytes::new();
+        buf.insert_faststr(s.clone());
+        let out = buf.into_faststr().unwrap();
+        assert_eq!(out, s);
+        assert_eq!(out.as_ptr(), s.as_ptr());
+
+        let mut buf = LinkedBytes::new();
+        buf.insert_faststr(s.clone());
+        buf.put_slice(b"!");
+        assert!(buf.into_faststr().is_none());
+        assert!(mixed().into_faststr().is_none());
+        assert!(LinkedBytes::new().into_faststr().is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "safe-only"))]
+    fn insert_borrowed_unchecked_is_zero_copy() {
+        let ring = vec![b'r'; 32];
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"head-");
+        // SAFETY: `ring` outlives `buf`, and isn't modified while it's alive
+        unsafe { buf.insert_borrowed_unchecked(&ring[8..16]) };
+        unsafe { buf.insert_borrowed_unchecked(&[]) };
+        buf.put_slice(b"-tail");
+        assert_eq!(buf.node_count(), 2);
+        assert_eq!(
+            buf.iter_chunks().nth(1).unwrap().as_ptr(),
+            ring[8..].as_ptr()
+        );
+        assert!(buf == "head-rrrrrrrr-tail");
+        assert!(!buf.is_all_zero_copy());
+
+        // the borrowed memory is copied when the content is cloned
+        let mut dst = LinkedBytes::new();
+        buf.clone_into(&mut dst);
+        assert!(dst == "head-rrrrrrrr-tail");
+        assert!(dst
+            .iter_chunks()
+            .all(|chunk| !chunk.as_ptr_range().contains(&ring[8..].as_ptr())));
+
+        buf.reset();
+        assert!(buf.is_empty());
+        drop(buf);
+        drop(ring);
+    }
+
+    #[test]
+    fn bytes_mut_with_ensures_spare_capacity() {
+        let mut buf = LinkedBytes::with_capacity(8);
+        buf.put_slice(b"12345678");
+        buf.insert(Bytes::from_static(b"bytes"));
+        let bytes = buf.bytes_mut_with(100);
+        assert!(bytes.capacity() - bytes.len() >= 100);
+        bytes.extend_from_slice(b"direct");
+        assert!(buf == "12345678bytesdirect");
+        assert_eq!(buf.len(), 19);
+    }
+
+    #[test]
+    fn chunks_covered_by_matches_the_write_loop() {
+        let buf = mixed();
+        // "head-" | "bytes-" | "static-" | "mid-" | "faststr-" | "tail"
+        assert_eq!(buf.chunks_covered_by(0), (0, 0));
+        assert_eq!(buf.chunks_covered_by(3), (0, 3));
+        assert_eq!(buf.chunks_covered_by(5), (1, 0));
+        assert_eq!(buf.chunks_covered_by(12), (2, 1));
+        assert_eq!(buf.chunks_covered_by(buf.len()), (6, 0));
+        assert_eq!(buf.chunks_covered_by(buf.len() + 10), (6, 0));
+
+        // the same as the io slices advanced by a partial write
+        for n in 0..=buf.len() {
+            let mut slices = buf.io_slice();
+            let mut slices = &mut slices[..];
+            IoSlice::advance_slices(&mut slices, n);
+            let (whole, offset) = buf.chunks_covered_by(n);
+            assert_eq!(slices.len(), 6 - whole, "{n}");
+            if let Some(first) = slices.first() {
+                assert_eq!(
+                    first.len(),
+                    buf.iter_chunks().nth(whole).unwrap().len() - offset
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn write_paths_agree_on_partial_writes() {
+        let strategies = [
+            WritevStrategy::DEFAULT,
+            WritevStrategy {
+                iov_max: 2,
+                ..WritevStrategy::DEFAULT
+            },
+            WritevStrategy {
+                max_batch_bytes: 6,
+                ..WritevStrategy::DEFAULT
+            },
+        ];
+        // with and without the `safe-only` feature, the writes must be the same
+        for strategy in strategies {
+            for script in scripts() {
+                let mut buf = mixed();
+                buf.set_writev_strategy(strategy);
+                let mut sync_writer = MockVectoredWriter::new(script.clone());
+                buf.sync_write_all_vectored(&mut sync_writer).unwrap();
+                let mut async_writer = MockVectoredWriter::new(script.clone());
+                buf.write_all_vectored(&mut async_writer).await.unwrap();
+                assert_eq!(sync_writer.data, buf.concat());
+                assert_eq!(async_writer.data, buf.concat());
+                buf.reset();
+            }
+
+            // the failed chunk is the same as well
+            for n in 0..mixed().len() {
+                let mut buf = mixed();
+                buf.set_writev_strategy(strategy);
+                let script = std::iter::repeat_n(Step::Accept(1), n)
+                    .chain([Step::Fail(std::io::ErrorKind::BrokenPipe)]);
+                let mut writer = MockVectoredWriter::new(script);
+                let e = buf
+                    .sync_write_all_vectored_detailed(&mut writer)
+                    .unwrap_err();
+                assert_eq!(e.bytes_written(), n);
+                assert_eq!(e.last_chunk_index(), buf.chunks_covered_by(n).0);
+            }
+        }
+    }
+
+    #[test]
+    fn chunk_at_boundaries() {
+        let buf = mixed();
+        // "head-" | "bytes-" | "static-" | "mid-" | "faststr-" | "tail"
+        assert_eq!(buf.chunk_at(0), Some((&b"head-"[..], 0)));
+        assert_eq!(buf.chunk_at(4), Some((&b"head-"[..], 4)));
+        assert_eq!(buf.chunk_at(5), Some((&b"bytes-"[..], 0)));
+        assert_eq!(buf.chunk_at(13), Some((&b"static-"[..], 2)));
+        assert_eq!(buf.chunk_at(buf.len() - 1), Some((&b"tail"[..], 3)));
+        assert_eq!(buf.chunk_at(buf.len()), None);
+        let content = buf.concat();
+        for (offset, &byte) in content.iter().enumerate() {
+            let (chunk, index) = buf.chunk_at(offset).unwrap();
+            assert_eq!(chunk[index], byte);
+        }
+        assert_eq!(LinkedBytes::new().chunk_at(0), None);
+    }
+
+    #[test]
+    fn chunk_mut_is_never_empty() {
+        let mut buf = LinkedBytes::with_capacity(4);
+        buf.set_carry_over_capacity(Some(0));
+        buf.put_slice(b"full");
+        buf.insert(Bytes::from_static(b"bytes"));
+        assert_eq!(buf.writable_chunk_len(), 0);
+        assert!(buf.remaining_mut() > 0);
+        assert!(buf.chunk_mut().len() > 0);
+
+        let mut buf = LinkedBytes::empty();
+        assert!(buf.chunk_mut().len() > 0);
+    }
+
+    #[test]
+    fn flatten_if_small_threshold() {
+        let mut buf = mixed();
+        let len = buf.len();
+        buf.flatten_if_small(len);
+        assert_eq!(buf.chunks_count(), 1);
+        assert_eq!(buf.node_count(), 0);
+        assert!(buf == "head-bytes-static-mid-faststr-tail");
+        buf.verify_invariants();
+
+        let mut buf = mixed();
+        buf.flatten_if_small(len - 1);
+        assert_eq!(buf.chunks_count(), 6);
+        assert!(buf == "head-bytes-static-mid-faststr-tail");
+    }
+
+    #[test]
+    fn shared_chunk_count_of_mixed() {
+        let mut buf = mixed();
+        // "bytes-" and "faststr-"
+        assert_eq!(buf.shared_chunk_count(), 2);
+        buf.insert(Bytes::new());
+        buf.insert_front(Bytes::from_static(b"front"));
+        assert_eq!(buf.shared_chunk_count(), 3);
+        assert_eq!(LinkedBytes::new().shared_chunk_count(), 0);
+    }
+
+    #[test]
+    fn extend_from_io_slices_copies() {
+        let mut buf = LinkedBytes::new();
+        buf.insert(Bytes::from_static(b"bytes-"));
+        let parts = [&b"one-"[..], b"", b"two-", b"three"];
+        let slices: Vec<IoSlice<'_>> = parts.iter().map(|part| IoSlice::new(part)).collect();
+        buf.extend_from_io_slices(&slices);
+        assert!(buf == "bytes-one-two-three");
+        assert_eq!(buf.chunks_count(), 2);
+        assert_eq!(&buf.bytes()[..], b"one-two-three");
+
+        // also through `io::Write::write_vectored`
+        let n = std::io::Write::write_vectored(&mut buf, &slices).unwrap();
+        assert_eq!(n, 13);
+        assert_eq!(buf.len(), 6 + 26);
+    }
+
+    #[test]
+    fn reset_shrinks_a_grown_deque() {
+        let mut buf = LinkedBytes::new();
+        for _ in 0..1000 {
+            buf.insert(Bytes::from_static(b"x"));
+        }
+        assert!(buf.list.capacity() >= 1000);
+        buf.reset();
+        assert!(buf.list.capacity() < DEFAULT_DEQUE_SHRINK_THRESHOLD);
+
+        // below the threshold, the deque is kept
+        buf.set_deque_shrink_threshold(usize::MAX);
+        for _ in 0..1000 {
+            buf.insert(Bytes::from_static(b"x"));
+        }
+        let capacity = buf.list.capacity();
+        buf.reset();
+        assert_eq!(buf.list.capacity(), capacity);
+    }
+
+    #[test]
+    fn merge_trailing_threshold_reduces_nodes() {
+        let build = |threshold| {
+            let mut buf = LinkedBytes::new();
+            buf.set_merge_trailing_threshold(threshold);
+            for i in 0..10u8 {
+                buf.put_slice(&[i; 3]);
+                // a node with room for the following small write
+                let mut node = BytesMut::with_capacity(64);
+                node.extend_from_slice(b"bytes");
+                buf.insert_bytes_mut(node);
+            }
+            buf.put_slice(b"tail");
+            buf
+        };
+        let plain = build(0);
+        let merged = build(8);
+        assert_eq!(plain.node_count(), 20);
+        assert_eq!(merged.node_count(), 11);
+        assert_eq!(merged.concat(), plain.concat());
+        merged.verify_invariants();
+    }
+
+    #[tokio::test]
+    async fn trailing_only_writes_skip_the_iovecs() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"only the bytes_mut");
+
+        let mut writer = RecordingWriter::default();
+        buf.write_all_vectored(&mut writer).await.unwrap();
+        assert_eq!(writer.data, b"only the bytes_mut");
+        assert!(writer.vectored_calls.is_empty());
+        assert_eq!(writer.plain_calls, [18]);
+
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.data, b"only the bytes_mut");
+        assert!(writer.vectored_calls.is_empty());
+        #[cfg(not(feature = "safe-only"))]
+        assert!(buf.ioslice.is_empty());
+
+        // with nodes, the vectored path is used
+        buf.insert(Bytes::from_static(b"!"));
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.vectored_calls, [vec![18, 1]]);
+    }
+
+    #[test]
+    fn placeholders_filled_out_of_order() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"<");
+        let first = buf.insert_placeholder();
+        buf.put_slice(b"|");
+        buf.insert(Bytes::from_static(b"mid"));
+        let second = buf.insert_placeholder();
+        buf.put_slice(b">");
+        // a front insert shifts the nodes
+        buf.insert_front(Bytes::from_static(b"^"));
+
+        buf.fill_placeholder(second, Bytes::from_static(b"two"));
+        buf.fill_placeholder(first, Bytes::from_static(b"one"));
+        assert!(buf == "^<one|midtwo>");
+        assert_eq!(buf.len(), 13);
+        buf.verify_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "placeholder handle is invalidated")]
+    fn placeholder_filled_twice() {
+        let mut buf = LinkedBytes::new();
+        let handle = buf.insert_placeholder();
+        buf.fill_placeholder(handle, Bytes::from_static(b"one"));
+        buf.fill_placeholder(handle, Bytes::from_static(b"two"));
+    }
+
+    #[test]
+    #[should_panic(expected = "placeholder handle is invalidated")]
+    fn placeholder_handle_used_after_reset() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"a");
+        let stale = buf.insert_placeholder();
+        buf.reset();
+        // the new placeholder has the same position as the stale one
+        buf.put_slice(b"b");
+        let _ = buf.insert_placeholder();
+        buf.fill_placeholder(stale, Bytes::from_static(b"STALE"));
+    }
+
+    #[test]
+    fn placeholder_survives_consume_front_nodes() {
+        let mut buf = LinkedBytes::new();
+        buf.insert_static(b"flushed-");
+        buf.insert_static(b"too-");
+        let first = buf.insert_placeholder();
+        buf.put_slice(b"|");
+        let second = buf.insert_placeholder();
+        buf.put_slice(b">");
+        buf.consume_front_nodes(2);
+        buf.fill_placeholder(second, Bytes::from_static(b"two"));
+        buf.fill_placeholder(first, Bytes::from_static(b"one"));
+        assert!(buf == "one|two>");
+        buf.verify_invariants();
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn crc32_trailer_excludes_itself() {
+        let mut buf = mixed();
+        let crc = crc32fast::hash(&buf.concat());
+        buf.append_crc32_trailer(true);
+        let content = buf.concat();
+        let (body, trailer) = content.split_at(content.len() - 4);
+        assert_eq!(body, mixed().concat());
+        assert_eq!(trailer, crc.to_be_bytes());
+
+        let mut buf = mixed();
+        buf.append_crc32_trailer(false);
+        assert_eq!(buf.concat()[buf.len() - 4..], crc.to_le_bytes());
+    }
+
+    #[test]
+    fn freeze_fast_paths() {
+        let bytes = Bytes::from(vec![1; 100]);
+        let mut buf = LinkedBytes::new();
+        buf.insert(bytes.clone());
+        assert_eq!(buf.freeze().as_ptr(), bytes.as_ptr());
+
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"bytes_mut");
+        let ptr = buf.bytes().as_ptr();
+        let frozen = buf.into_bytes();
+        assert_eq!(frozen.as_ptr(), ptr);
+        assert_eq!(&frozen[..], b"bytes_mut");
+
+        assert_eq!(&mixed().freeze()[..], b"head-bytes-static-mid-faststr-tail");
+    }
+
+    #[test]
+    fn carry_over_capacity_after_a_full_buffer() {
+        let mut buf = LinkedBytes::with_capacity(64);
+        buf.put_slice(&[0; 64]);
+        buf.insert(Bytes::from(vec![1; 1024]));
+        // the capacity of the full buffer is carried over
+        assert!(buf.capacity() >= 64);
+        let ptr = buf.bytes().as_ptr();
+        buf.put_slice(b"small");
+        assert_eq!(buf.bytes().as_ptr(), ptr);
+
+        buf.set_carry_over_capacity(Some(0));
+        buf.put_slice(&[0; 59]);
+        buf.insert(Bytes::from_static(b"bytes"));
+        assert_eq!(buf.capacity(), 0);
+
+        buf.set_carry_over_capacity(Some(256));
+        buf.put_slice(b"x");
+        buf.insert(Bytes::from_static(b"bytes"));
+        assert!(buf.capacity() >= 256);
+        buf.verify_invariants();
+    }
+
+    #[tokio::test]
+    async fn write_prefix_keeps_the_remainder() {
+        let mut buf = mixed();
+        let mut writer = MockVectoredWriter::new([Step::Accept(4), Step::Skip]);
+        buf.write_prefix(&mut writer, 13).await.unwrap();
+        assert_eq!(writer.data, b"head-bytes-st");
+        assert!(buf == "atic-mid-faststr-tail");
+        assert_eq!(buf.len(), 21);
+        buf.verify_invariants();
+
+        buf.write_prefix(&mut writer, 0).await.unwrap();
+        let len = buf.len();
+        buf.write_prefix(&mut writer, len).await.unwrap();
+        assert!(buf.is_empty());
+        assert_eq!(writer.data, mixed().concat());
+    }
+
+    #[test]
+    fn buf_reads_across_the_list_and_the_bytes_mut() {
+        let mut buf = LinkedBytes::new();
+        buf.put_u16(0x0102);
+        buf.insert(Bytes::from_static(&[3, 4, 5]));
+        buf.put_slice(&[6, 7, 8, 9]);
+        assert_eq!(buf.remaining(), 9);
+        assert_eq!(buf.chunk(), [1, 2]);
+        assert_eq!(buf.get_u8(), 1);
+        // across the nodes
+        assert_eq!(buf.get_u32(), 0x0203_0405);
+        // across the last node and the bytes_mut
+        assert_eq!(buf.chunk(), [6, 7, 8, 9]);
+        buf.advance(1);
+        assert_eq!(buf.get_u16(), 0x0708);
+        assert_eq!(buf.remaining(), 1);
+        buf.advance(1);
+        assert!(!buf.has_remaining());
+        assert!(buf.chunk().is_empty());
+        buf.verify_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot advance past `remaining`")]
+    fn buf_advance_past_remaining() {
+        let mut buf = mixed();
+        let len = buf.len();
+        buf.advance(len + 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "safe-only"))]
+    fn with_layout_doesnt_reallocate() {
+        const CHUNKS: usize = 32;
+        let mut buf = LinkedBytes::with_layout(16, CHUNKS);
+        let capacities = (buf.capacity(), buf.list.capacity(), buf.ioslice.capacity());
+        let ptr = buf.bytes().as_ptr();
+        buf.put_slice(&[0; 16]);
+        for i in 0..CHUNKS {
+            buf.insert(Bytes::from_static(b"chunk"));
+            // a small write between the inserts needs one more node
+            buf.put_u8(i as u8);
+        }
+        assert_eq!(buf.iter_chunks().next().unwrap().as_ptr(), ptr);
+        assert_eq!(buf.list.capacity(), capacities.1);
+
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(buf.ioslice.capacity(), capacities.2);
+        assert_eq!(writer.data, buf.concat());
+        assert!(capacities.0 >= 16);
+    }
+
+    #[tokio::test]
+    async fn insert_front_after_buf_mut_writes() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"body");
+        buf.insert(Bytes::from_static(b"-bytes"));
+        buf.put_slice(b"-tail");
+        buf.insert_faststr_front(FastStr::from_static_str("len:15|"));
+        buf.insert_front(Bytes::from_static(b"HDR|"));
+        assert!(buf == "HDR|len:15|body-bytes-tail");
+        assert_eq!(&buf.io_slice()[0][..], b"HDR|");
+        assert_eq!(&buf.io_slice()[1][..], b"len:15|");
+
+        let mut writer = RecordingWriter::default();
+        buf.write_all_vectored(&mut writer).await.unwrap();
+        assert_eq!(writer.vectored_calls[0][..2], [4, 7]);
+        assert_eq!(writer.data, buf.concat());
+
+        // the head node is not a `BytesMut`
+        buf.reset();
+        assert!(buf.is_empty());
+        buf.put_slice(b"again");
+        assert!(buf == "again");
+    }
+
+    /// Sums up the lengths of all the nodes and the bytes_mut, without the cached length.
+    fn brute_force_len(buf: &LinkedBytes) -> usize {
+        buf.iter_list()
+            .map(|node| node.as_ref().len())
+            .sum::<usize>()
+            + buf.bytes().len()
+    }
+
+    #[test]
+    fn cached_len_matches_brute_force() {
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        let mut next = move |n: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n as u64) as usize
+        };
+
+        let mut buf = LinkedBytes::new();
+        for _ in 0..2000 {
+            match next(8) {
+                0 => buf.put_slice(&[1; 7][..next(8)]),
+                1 => buf.insert(Bytes::from_static(&[2; 9][..next(10)])),
+                2 => buf.insert_faststr(FastStr::from_static_str(&"faststr"[..next(8)])),
+                3 => {
+                    let _ = buf.split();
+                }
+                4 => buf.put_u32(3),
+                5 => {
+                    let n = next(buf.len() + 1);
+                    buf.advance(n);
+                }
+                6 => buf.insert_front(Bytes::from_static(b"front")),
+                _ => buf.reset(),
+            }
+            assert_eq!(buf.len(), brute_force_len(&buf));
+            assert_eq!(buf.is_empty(), brute_force_len(&buf) == 0);
+            assert_eq!(Some(buf.len()), buf.try_len());
+        }
+    }
+
+    #[test]
+    fn insert_bytes_mut_reset_reclaims_like_insert() {
+        let mut buf = LinkedBytes::with_capacity(256);
+        let ptr = buf.bytes().as_ptr();
+        buf.put_slice(b"head-");
+        let mut owned = BytesMut::with_capacity(64);
+        owned.extend_from_slice(b"owned");
+        let owned_ptr = owned.as_ptr();
+        buf.insert_bytes_mut(owned);
+        buf.put_slice(b"-tail");
+        assert!(buf == "head-owned-tail");
+        // not copied
+        assert_eq!(buf.iter_chunks().nth(1).unwrap().as_ptr(), owned_ptr);
+
+        // the nodes split from the bytes_mut are merged back around the foreign buffer
+        buf.reset();
+        assert_eq!(buf.bytes().as_ptr(), ptr);
+        assert_eq!(buf.capacity(), 256);
+    }
+
+    #[test]
+    fn truncate_nodes_keeps_the_first_chunks() {
+        let mut buf = LinkedBytes::new();
+        for chunk in ["aa", "bb", "cc", "dd"] {
+            buf.insert(Bytes::from_static(chunk.as_bytes()));
+        }
+        buf.put_slice(b"ee");
+        assert_eq!(buf.chunks_count(), 5);
+
+        buf.truncate_nodes(2);
+        assert!(buf == "aabb");
+        assert_eq!(buf.node_count(), 2);
+        assert_eq!(buf.len(), 4);
+        buf.verify_invariants();
+
+        // nothing to truncate
+        buf.put_slice(b"ff");
+        buf.truncate_nodes(2);
+        assert!(buf == "aabbff");
+        buf.truncate_nodes(0);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn reset_keeps_capacity_with_interleaved_inserts() {
+        for capacity in [64, 1024, DEFAULT_BUFFER_SIZE] {
+            let mut buf = LinkedBytes::with_capacity(capacity);
+            let before = buf.capacity();
+            for _ in 0..100 {
+                for i in 0..5u8 {
+                    buf.put_slice(&[i; 3]);
+                    buf.insert(Bytes::from_static(b"bytes"));
+                    buf.insert_faststr(FastStr::from_static_str("faststr"));
+                }
+                buf.reset();
+                assert!(buf.capacity() >= before, "{capacity}");
+            }
+        }
+    }
+
+    #[test]
+    fn merge_into_moves_nodes() {
+        let mut dst = LinkedBytes::new();
+        dst.put_slice(b"dst-");
+        let src = mixed();
+        let shared = src.iter_chunks().nth(1).unwrap().as_ptr();
+        let count = src.chunks_count();
+        src.merge_into(&mut dst);
+        assert!(dst == "dst-head-bytes-static-mid-faststr-tail");
+        assert_eq!(dst.chunks_count(), 1 + count);
+        // zero-copy for the shared nodes
+        assert_eq!(dst.iter_chunks().nth(2).unwrap().as_ptr(), shared);
+        dst.verify_invariants();
+
+        LinkedBytes::new().merge_into(&mut dst);
+        assert_eq!(dst.chunks_count(), 1 + count);
+    }
+
+    #[test]
+    fn consecutive_inserts_dont_push_empty_nodes() {
+        let mut buf = LinkedBytes::new();
+        for _ in 0..10 {
+            buf.insert(Bytes::from_static(b"bytes"));
+            buf.insert_faststr(FastStr::from_static_str("faststr"));
+        }
+        assert_eq!(buf.node_count(), 20);
+        assert!(buf.iter_list().all(|node| !node.as_ref().is_empty()));
+        buf.reset();
+        assert!(buf.is_empty());
+        assert_eq!(buf.node_count(), 0);
+        buf.put_slice(b"reused");
+        assert!(buf == "reused");
+    }
+
+    #[tokio::test]
+    async fn concat_matches_the_vectored_write() {
+        let mut single = LinkedBytes::new();
+        single.insert(Bytes::from_static(b"single"));
+        for mut buf in [mixed(), single, LinkedBytes::new()] {
+            let mut writer = RecordingWriter::default();
+            buf.write_all_vectored(&mut writer).await.unwrap();
+            let concat = buf.concat();
+            assert_eq!(concat, writer.data);
+            assert_eq!(concat.len(), buf.len());
+            buf.reset();
+        }
+
+        let bytes = Bytes::from_static(b"single");
+        let buf = LinkedBytes::from(bytes.clone());
+        assert_eq!(buf.into_bytes().as_ptr(), bytes.as_ptr());
+    }
+
+    #[test]
+    fn reset_with_faststr_and_static_heads() {
+        let mut buf = LinkedBytes::empty();
+        buf.insert_faststr(FastStr::from_static_str("faststr"));
+        buf.put_slice(b"x");
+        buf.reset();
+        assert!(buf.is_empty());
+        assert_eq!(buf.node_count(), 0);
+
+        let mut buf = LinkedBytes::from_static_parts([&b"a"[..], b"b"]);
+        buf.reset();
+        assert!(buf.is_empty());
+        buf.put_slice(b"usable");
+        assert!(buf == "usable");
+    }
+
+    #[test]
+    fn len_after_every_mutation_path() {
+        let mut buf = LinkedBytes::new();
+        buf.put_slice(b"abc");
+        assert_eq!(buf.len(), 3);
+        // through `BufMut::chunk_mut` and `advance_mut`
+        let chunk = buf.chunk_mut();
+        chunk[..2].copy_from_slice(b"de");
+        unsafe { buf.advance_mut(2) };
+        assert_eq!(buf.len(), 5);
+        buf.insert(Bytes::from_static(b"bytes"));
+        assert_eq!(buf.len(), 10);
+        // directly through the bytes_mut
+        buf.bytes_mut().extend_from_slice(b"1234");
+        assert_eq!(buf.len(), 14);
+        // resizing a node through `get_list_mut`
+        if let Some(Node::BytesMut(node)) = buf.get_list_mut(0) {
+            node.truncate(1);
+        }
+        assert_eq!(buf.len(), 10);
+        assert_eq!(buf.len(), brute_force_len(&buf));
+        buf.verify_invariants();
+        let _ = buf.split();
+        assert_eq!(buf.len(), 10);
+        buf.reset();
+        assert_eq!(buf.len(), 0);
+        assert!(buf.is_empty());
+    }
+
+    #[tokio::test]
+    async fn non_default_writev_strategies() {
+        // small content is copied and written by one plain write
+        let mut buf = mixed();
+        buf.set_writev_strategy(WritevStrategy {
+            contiguous_fallback_threshold: 64,
+            ..WritevStrategy::default()
+        });
+        let mut writer = RecordingWriter::default();
+        buf.write_all_vectored(&mut writer).await.unwrap();
+        assert!(writer.vectored_calls.is_empty());
+        assert_eq!(writer.plain_calls, [buf.len()]);
+
+        // the slices are batched by count and by bytes
+        let mut buf = mixed();
+        buf.set_writev_strategy(WritevStrategy {
+            iov_max: 4,
+            max_batch_bytes: 12,
+            ..WritevStrategy::default()
+        });
+        let mut writer = RecordingWriter::default();
+        buf.sync_write_all_vectored(&mut writer).unwrap();
+        assert_eq!(writer.vectored_calls, [[5, 6], [7, 4], [8, 4]]);
+        assert_eq!(writer.data, buf.concat());
+        assert_eq!(WritevStrategy::default(), WritevStrategy::DEFAULT);
+    }
+
+    #[test]
+    fn size_limit_bounds_buf_mut_writes() {
+        let mut buf = LinkedBytes::new();
+        buf.set_size_limit(Some(10));
+        buf.put_slice(b"1234");
+        assert_eq!(buf.remaining_mut(), 6);
+        assert_eq!(buf.chunk_mut().len(), 6);
+        // a generic writer stops at the limit
+        let data = [b'x'; 32];
+        let mut src = &data[..];
+        while buf.has_remaining_mut() && src.has_remaining() {
+            let n = buf.chunk_mut().len().min(src.len());
+            buf.chunk_mut()[..n].copy_from_slice(&src[..n]);
+            unsafe { buf.advance_mut(n) };
+            src.advance(n);
+        }
+        assert_eq!(buf.len(), 10);
+        assert_eq!(buf.remaining_mut(), 0);
+        assert_eq!(buf.chunk_mut().len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "size limit exceeded")]
+    fn size_limit_put_slice_past_the_limit() {
+        let mut buf = LinkedBytes::new();
+        buf.set_size_limit(Some(4));
+        buf.put_slice(b"12345");
+    }
+
+    #[test]
+    fn extend_from_linked_io_slices() {
+        let mut first = LinkedBytes::new();
+        first.put_slice(b"first-");
+        first.insert_faststr(FastStr::from_static_str("faststr-"));
+        let second = mixed();
+        let expected = [&first.concat()[..], &second.concat()[..]].concat();
+        let count = first.chunks_count() + second.chunks_count();
+
+        first.extend_from_linked(second);
+        let content: Vec<u8> = first
+            .io_slice()
+            .iter()
+            .flat_map(|slice| slice.iter().copied())
+            .collect();
+        assert_eq!(content, expected);
+        assert_eq!(first.io_slice().len(), count);
+        // the variants are kept
+        assert!(first
+            .iter_list()
+            .any(|node| matches!(node, Node::Static(s) if *s == b"static-")));
+        first.verify_invariants();
+    }
+
+    #[test]
+    fn fmt_write_interleaved_with_inserts() {
+        use std::fmt::Write as _;
+
+        let mut buf = LinkedBytes::new();
+        write!(buf, "Content-Length: {}\r\n", 42).unwrap();
+        buf.insert(Bytes::from_static(b"body"));
+        buf.put_slice(b"\r\n");
+        write!(buf, "*{}\r\n", 3).unwrap();
+        buf.insert_faststr(FastStr::from_static_str("$3\r\nfoo\r\n"));
+        assert!(buf == "Content-Length: 42\r\nbody\r\n*3\r\n$3\r\nfoo\r\n");
+        let lens: Vec<usize> = buf.io_slice().iter().map(|slice| slice.len()).collect();
+        assert_eq!(lens, [20, 4, 6, 9]);
+    }
+
+    #[test]
+    fn io_write_never_fails() {
+        use std::io::Write as _;
+
+        let mut buf = LinkedBytes::new();
+        assert_eq!(buf.write(b"abc").unwrap(), 3);
+        buf.insert(Bytes::from_static(b"-"));
+        buf.write_all(b"def").unwrap();
+        buf.flush().unwrap();
+        assert!(buf == "abc-def");
     }
 }